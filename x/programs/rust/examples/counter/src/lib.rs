@@ -1,4 +1,4 @@
-use wasmlanche_sdk::{params, public, state_keys, types::Address, Context, Program};
+use wasmlanche_sdk::{params, prelude::*};
 
 #[state_keys]
 enum StateKeys {
@@ -13,7 +13,7 @@ pub fn initialize_address(context: Context, address: Address) -> bool {
 
     if program
         .state()
-        .get::<i64, _>(StateKeys::Counter(address))
+        .get::<u64, _>(StateKeys::Counter(address))
         .is_ok()
     {
         panic!("counter already initialized for address")
@@ -21,7 +21,7 @@ pub fn initialize_address(context: Context, address: Address) -> bool {
 
     program
         .state()
-        .store(StateKeys::Counter(address), &0_i64)
+        .store(StateKeys::Counter(address), &0_u64)
         .expect("failed to store counter");
 
     true
@@ -29,8 +29,10 @@ pub fn initialize_address(context: Context, address: Address) -> bool {
 
 /// Increments the count at the address by the amount.
 #[public]
-pub fn inc(context: Context, to: Address, amount: i64) -> bool {
-    let counter = amount + get_value(context, to);
+pub fn inc(context: Context, to: Address, amount: u64) -> bool {
+    let counter = get_value(context, to)
+        .checked_add(amount)
+        .expect("counter overflow");
     let Context { program } = context;
 
     program
@@ -43,14 +45,24 @@ pub fn inc(context: Context, to: Address, amount: i64) -> bool {
 
 /// Increments the count at the address by the amount for an external program.
 #[public]
-pub fn inc_external(_: Context, target: Program, max_units: i64, of: Address, amount: i64) -> i64 {
-    let params = params!(&of, &amount).unwrap();
-    target.call_function("inc", params, max_units).unwrap()
+pub fn inc_external(
+    _: Context,
+    target: Program,
+    #[allow_signed] max_units: i64,
+    of: Address,
+    amount: u64,
+) -> i64 {
+    target
+        .call("inc", max_units)
+        .arg(&of)
+        .arg(&amount)
+        .invoke()
+        .unwrap()
 }
 
 /// Gets the count at the address.
 #[public]
-pub fn get_value(context: Context, of: Address) -> i64 {
+pub fn get_value(context: Context, of: Address) -> u64 {
     let Context { program } = context;
     program
         .state()
@@ -60,7 +72,12 @@ pub fn get_value(context: Context, of: Address) -> i64 {
 
 /// Gets the count at the address for an external program.
 #[public]
-pub fn get_value_external(_: Context, target: Program, max_units: i64, of: Address) -> i64 {
+pub fn get_value_external(
+    _: Context,
+    target: Program,
+    #[allow_signed] max_units: i64,
+    of: Address,
+) -> i64 {
     let params = params!(&of).unwrap();
     target
         .call_function("get_value", params, max_units)
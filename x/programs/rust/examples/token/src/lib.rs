@@ -1,8 +1,21 @@
 use borsh::{BorshDeserialize, BorshSerialize};
-use wasmlanche_sdk::Context;
-use wasmlanche_sdk::{public, state_keys, types::Address};
+use wasmlanche_sdk::prelude::*;
 
-const INITIAL_SUPPLY: i64 = 123456789;
+const INITIAL_SUPPLY: u64 = 123456789;
+
+/// The number of decimals of the token, one whole token is `10^DECIMALS` base units.
+const DECIMALS: u8 = 9;
+
+/// The largest supported number of decimals. Above it a handful of whole
+/// tokens already overflow a `u64` of base units.
+const MAX_DECIMALS: u8 = 18;
+
+/// The number of base units in one whole token. Fails to compile if
+/// `DECIMALS` exceeds `MAX_DECIMALS`.
+const BASE_UNIT: u64 = match base_unit(DECIMALS) {
+    Some(unit) => unit,
+    None => panic!("DECIMALS must not exceed MAX_DECIMALS"),
+};
 
 /// The program state keys.
 #[state_keys]
@@ -15,12 +28,19 @@ enum StateKey {
     Symbol,
     /// The balance of the token by address. Key prefix 0x3 + address.
     Balance(Address),
+    /// The number of bytes stored by the program, excluding this key. Key prefix 0x4.
+    StateBytes,
 }
 
 /// Initializes the program with a name, symbol, and total supply.
 #[public]
 pub fn init(context: Context) -> bool {
     let Context { program } = context;
+    // a repeated init overwrites the same keys with values of the same size
+    let first_init = program
+        .state()
+        .get::<u64, _>(StateKey::TotalSupply)
+        .is_err();
 
     // set total supply
     program
@@ -40,12 +60,21 @@ pub fn init(context: Context) -> bool {
         .store(StateKey::Symbol, b"WACK")
         .expect("failed to store symbol");
 
+    if first_init {
+        grow_state_size(
+            program,
+            entry_size(StateKey::TotalSupply, &INITIAL_SUPPLY)
+                + entry_size(StateKey::Name, b"WasmCoin")
+                + entry_size(StateKey::Symbol, b"WACK"),
+        );
+    }
+
     true
 }
 
 /// Returns the total supply of the token.
 #[public]
-pub fn get_total_supply(context: Context) -> i64 {
+pub fn get_total_supply(context: Context) -> u64 {
     let Context { program } = context;
     program
         .state()
@@ -55,16 +84,22 @@ pub fn get_total_supply(context: Context) -> i64 {
 
 /// Transfers balance from the token owner to the recipient.
 #[public]
-pub fn mint_to(context: Context, recipient: Address, amount: i64) -> bool {
+pub fn mint_to(context: Context, recipient: Address, amount: u64) -> bool {
     let Context { program } = context;
-    let balance = program
-        .state()
-        .get::<i64, _>(StateKey::Balance(recipient))
-        .unwrap_or_default();
+    let balance = match program.state().get::<u64, _>(StateKey::Balance(recipient)) {
+        Ok(balance) => balance,
+        Err(_) => {
+            grow_state_size(program, entry_size(StateKey::Balance(recipient), &0_u64));
+            0
+        }
+    };
 
     program
         .state()
-        .store(StateKey::Balance(recipient), &(balance + amount))
+        .store(
+            StateKey::Balance(recipient),
+            &balance.checked_add(amount).expect("balance overflow"),
+        )
         .expect("failed to store balance");
 
     true
@@ -74,6 +109,10 @@ pub fn mint_to(context: Context, recipient: Address, amount: i64) -> bool {
 #[public]
 pub fn burn_from(context: Context, recipient: Address) -> bool {
     let Context { program } = context;
+    if let Ok(balance) = program.state().get::<u64, _>(StateKey::Balance(recipient)) {
+        shrink_state_size(program, entry_size(StateKey::Balance(recipient), &balance));
+    }
+
     program
         .state()
         .delete(StateKey::Balance(recipient))
@@ -83,22 +122,25 @@ pub fn burn_from(context: Context, recipient: Address) -> bool {
 
 /// Transfers balance from the sender to the the recipient.
 #[public]
-pub fn transfer(context: Context, sender: Address, recipient: Address, amount: i64) -> bool {
+pub fn transfer(context: Context, sender: Address, recipient: Address, amount: u64) -> bool {
     let Context { program } = context;
     assert_ne!(sender, recipient, "sender and recipient must be different");
 
     // ensure the sender has adequate balance
     let sender_balance = program
         .state()
-        .get::<i64, _>(StateKey::Balance(sender))
+        .get::<u64, _>(StateKey::Balance(sender))
         .expect("failed to update balance");
 
-    assert!(amount >= 0 && sender_balance >= amount, "invalid input");
+    assert!(sender_balance >= amount, "invalid input");
 
-    let recipient_balance = program
-        .state()
-        .get::<i64, _>(StateKey::Balance(recipient))
-        .unwrap_or_default();
+    let recipient_balance = match program.state().get::<u64, _>(StateKey::Balance(recipient)) {
+        Ok(balance) => balance,
+        Err(_) => {
+            grow_state_size(program, entry_size(StateKey::Balance(recipient), &0_u64));
+            0
+        }
+    };
 
     // update balances
     program
@@ -108,7 +150,12 @@ pub fn transfer(context: Context, sender: Address, recipient: Address, amount: i
 
     program
         .state()
-        .store(StateKey::Balance(recipient), &(recipient_balance + amount))
+        .store(
+            StateKey::Balance(recipient),
+            &recipient_balance
+                .checked_add(amount)
+                .expect("balance overflow"),
+        )
         .expect("failed to store balance");
 
     true
@@ -117,14 +164,14 @@ pub fn transfer(context: Context, sender: Address, recipient: Address, amount: i
 #[derive(BorshDeserialize, BorshSerialize)]
 pub struct Minter {
     to: Address,
-    amount: i32,
+    amount: u64,
 }
 
 /// Mints tokens to multiple recipients.
 #[public]
 pub fn mint_to_many(context: Context, minters: Vec<Minter>) -> bool {
     for minter in minters.iter() {
-        mint_to(context, minter.to, minter.amount as i64);
+        mint_to(context, minter.to, minter.amount);
     }
 
     true
@@ -132,7 +179,7 @@ pub fn mint_to_many(context: Context, minters: Vec<Minter>) -> bool {
 
 /// Gets the balance of the recipient.
 #[public]
-pub fn get_balance(context: Context, recipient: Address) -> i64 {
+pub fn get_balance(context: Context, recipient: Address) -> u64 {
     let Context { program } = context;
     program
         .state()
@@ -140,14 +187,213 @@ pub fn get_balance(context: Context, recipient: Address) -> i64 {
         .unwrap_or_default()
 }
 
+/// Returns the number of bytes used by the program's keys and values.
+#[public]
+pub fn state_size(context: Context) -> u64 {
+    let Context { program } = context;
+    program
+        .state()
+        .get(StateKey::StateBytes)
+        .unwrap_or_default()
+}
+
+/// Converts whole tokens to base units. Returns `None` on overflow.
+pub fn to_base_units(whole: u64) -> Option<u64> {
+    whole.checked_mul(BASE_UNIT)
+}
+
+/// Converts base units to whole tokens, dropping any fraction of a token.
+pub fn from_base_units(base: u64) -> u64 {
+    base / BASE_UNIT
+}
+
+/// Returns the number of base units in one whole token with `decimals`,
+/// or `None` if `decimals` exceeds `MAX_DECIMALS`.
+const fn base_unit(decimals: u8) -> Option<u64> {
+    if decimals > MAX_DECIMALS {
+        None
+    } else {
+        10_u64.checked_pow(decimals as u32)
+    }
+}
+
+/// Returns the number of bytes a key and its serialized value take up in storage.
+fn entry_size<V: BorshSerialize>(key: StateKey, value: &V) -> u64 {
+    let value = borsh::to_vec(value).expect("failed to serialize value");
+    (key.to_vec().len() + value.len()) as u64
+}
+
+/// Adds `bytes` to the tracked state size.
+fn grow_state_size(program: Program, bytes: u64) {
+    let size = state_size(Context { program });
+    program
+        .state()
+        .store(StateKey::StateBytes, &(size + bytes))
+        .expect("failed to store state size");
+}
+
+/// Subtracts `bytes` from the tracked state size.
+fn shrink_state_size(program: Program, bytes: u64) {
+    let size = state_size(Context { program });
+    program
+        .state()
+        .store(StateKey::StateBytes, &size.saturating_sub(bytes))
+        .expect("failed to store state size");
+}
+
 #[cfg(test)]
 mod tests {
     use simulator::{Endpoint, Key, Param, Plan, Require, ResultAssertion, Step};
 
-    use crate::INITIAL_SUPPLY;
+    use crate::{base_unit, from_base_units, to_base_units, BASE_UNIT, DECIMALS, INITIAL_SUPPLY};
 
     const PROGRAM_PATH: &str = env!("PROGRAM_PATH");
 
+    #[test]
+    fn base_units_round_trip() {
+        assert_eq!(BASE_UNIT, 10_u64.pow(DECIMALS.into()));
+        assert_eq!(to_base_units(5), Some(5 * BASE_UNIT));
+        assert_eq!(from_base_units(5 * BASE_UNIT), 5);
+        assert_eq!(from_base_units(2 * BASE_UNIT + 1), 2);
+        assert_eq!(to_base_units(u64::MAX), None);
+    }
+
+    #[test]
+    fn decimals_above_max_are_rejected() {
+        assert_eq!(base_unit(18), Some(1_000_000_000_000_000_000));
+        assert_eq!(base_unit(19), None);
+    }
+
+    /// Golden encodings of the program's arguments and state. Off-chain callers
+    /// rely on these bytes, so a failure here means the ABI changed.
+    mod vectors {
+        use borsh::{BorshDeserialize, BorshSerialize};
+        use std::fmt::Debug;
+        use wasmlanche_sdk::{types::Address, Context};
+
+        use crate::{Minter, StateKey, INITIAL_SUPPLY};
+
+        const ALICE: [u8; 32] = [0xaa; 32];
+        const BOB: [u8; 32] = [0xbb; 32];
+
+        /// Decodes `bytes` and checks that encoding the value gives the same bytes back.
+        fn decode<T>(bytes: &[u8]) -> T
+        where
+            T: BorshDeserialize + BorshSerialize,
+        {
+            let value = borsh::from_slice(bytes).unwrap();
+            assert_eq!(borsh::to_vec(&value).unwrap(), bytes);
+            value
+        }
+
+        fn assert_context(program_id: [u8; 32]) {
+            let context: Context = decode(&program_id);
+            assert_eq!(context.program.id(), &program_id);
+        }
+
+        fn assert_decodes<T>(bytes: &[u8], expected: &T)
+        where
+            T: BorshDeserialize + BorshSerialize + PartialEq + Debug,
+        {
+            assert_eq!(&decode::<T>(bytes), expected);
+        }
+
+        // Every function takes the context as its first argument, and the
+        // other arguments each come in their own buffer. init, get_total_supply
+        // and state_size take only the context. The functions return wasm
+        // scalars, so the stored values are the only encoded outputs.
+
+        // init(context)
+        #[test]
+        fn init_arguments() {
+            assert_context([0x11; 32]);
+        }
+
+        // get_total_supply(context) -> u64
+        #[test]
+        fn get_total_supply_arguments() {
+            assert_context([0x12; 32]);
+        }
+
+        // state_size(context) -> u64
+        #[test]
+        fn state_size_arguments() {
+            assert_context([0x13; 32]);
+        }
+
+        // mint_to(context, recipient: Address, amount: u64)
+        #[test]
+        fn mint_to_arguments() {
+            assert_decodes(&ALICE, &Address::new(ALICE));
+            assert_decodes(&[0xe8, 0x03, 0, 0, 0, 0, 0, 0], &1000_u64);
+        }
+
+        // burn_from(context, recipient: Address)
+        #[test]
+        fn burn_from_arguments() {
+            assert_decodes(&BOB, &Address::new(BOB));
+        }
+
+        // transfer(context, sender: Address, recipient: Address, amount: u64)
+        #[test]
+        fn transfer_arguments() {
+            assert_decodes(&ALICE, &Address::new(ALICE));
+            assert_decodes(&BOB, &Address::new(BOB));
+            assert_decodes(&[0x64, 0, 0, 0, 0, 0, 0, 0], &100_u64);
+        }
+
+        // get_balance(context, recipient: Address) -> u64
+        #[test]
+        fn get_balance_arguments() {
+            assert_decodes(&ALICE, &Address::new(ALICE));
+        }
+
+        // mint_to_many(context, minters: Vec<Minter>)
+        #[test]
+        fn mint_to_many_arguments() {
+            let bytes = [
+                &[2, 0, 0, 0][..],
+                &ALICE,
+                &[1, 0, 0, 0, 0, 0, 0, 0],
+                &BOB,
+                &[2, 0, 0, 0, 0, 0, 0, 0],
+            ]
+            .concat();
+
+            let minters: Vec<Minter> = decode(&bytes);
+            let decoded = minters
+                .iter()
+                .map(|minter| (minter.to, minter.amount))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                decoded,
+                vec![(Address::new(ALICE), 1), (Address::new(BOB), 2)]
+            );
+        }
+
+        #[test]
+        fn state_keys() {
+            assert_eq!(StateKey::TotalSupply.to_vec(), [0]);
+            assert_eq!(StateKey::Name.to_vec(), [1]);
+            assert_eq!(StateKey::Symbol.to_vec(), [2]);
+            assert_eq!(
+                StateKey::Balance(Address::new(ALICE)).to_vec(),
+                [&[3][..], &ALICE].concat()
+            );
+            assert_eq!(StateKey::StateBytes.to_vec(), [4]);
+        }
+
+        #[test]
+        fn stored_values() {
+            assert_eq!(
+                borsh::to_vec(&INITIAL_SUPPLY).unwrap(),
+                [0x15, 0xcd, 0x5b, 0x07, 0, 0, 0, 0]
+            );
+            // the name and symbol are byte arrays, stored without a length
+            assert_eq!(borsh::to_vec(b"WasmCoin").unwrap(), b"WasmCoin");
+        }
+    }
+
     #[test]
     fn create_program() {
         let simulator = simulator::Client::new();
@@ -252,7 +498,7 @@ mod tests {
             max_units: 0,
             params: vec![program_id.into()],
             require: Some(Require {
-                result: ResultAssertion::NumericEq(INITIAL_SUPPLY as u64),
+                result: ResultAssertion::NumericEq(INITIAL_SUPPLY),
             }),
         });
 
@@ -305,4 +551,114 @@ mod tests {
                 .next()
         );
     }
+
+    #[test]
+    fn state_size_tracks_mint_and_burn() {
+        let simulator = simulator::Client::new();
+
+        let owner_key_id = String::from("owner");
+        let alice_key = Param::Key(Key::Ed25519(String::from("alice")));
+
+        // total supply (1 + 8), name (1 + 8) and symbol (1 + 4)
+        let initial_size = 23;
+        // balance prefix and address (1 + 32) and the balance (8)
+        let balance_size = 41;
+
+        let mut plan = Plan::new(owner_key_id.clone());
+
+        plan.add_step(Step::create_key(Key::Ed25519(owner_key_id)));
+        let program_id = plan.add_step(Step::create_program(PROGRAM_PATH));
+
+        plan.add_step(Step {
+            endpoint: Endpoint::Key,
+            method: "key_create".into(),
+            params: vec![alice_key.clone()],
+            max_units: 0,
+            require: None,
+        });
+
+        plan.add_step(Step {
+            endpoint: Endpoint::Execute,
+            method: "init".into(),
+            params: vec![program_id.into()],
+            max_units: 1000000,
+            require: None,
+        });
+
+        plan.add_step(Step {
+            endpoint: Endpoint::ReadOnly,
+            method: "state_size".into(),
+            max_units: 0,
+            params: vec![program_id.into()],
+            require: Some(Require {
+                result: ResultAssertion::NumericEq(initial_size),
+            }),
+        });
+
+        // running init again does not count the overwritten keys twice
+        plan.add_step(Step {
+            endpoint: Endpoint::Execute,
+            method: "init".into(),
+            params: vec![program_id.into()],
+            max_units: 1000000,
+            require: None,
+        });
+
+        plan.add_step(Step {
+            endpoint: Endpoint::ReadOnly,
+            method: "state_size".into(),
+            max_units: 0,
+            params: vec![program_id.into()],
+            require: Some(Require {
+                result: ResultAssertion::NumericEq(initial_size),
+            }),
+        });
+
+        plan.add_step(Step {
+            endpoint: Endpoint::Execute,
+            method: "mint_to".into(),
+            params: vec![program_id.into(), alice_key.clone(), Param::U64(1000)],
+            max_units: 1000000,
+            require: None,
+        });
+
+        plan.add_step(Step {
+            endpoint: Endpoint::ReadOnly,
+            method: "state_size".into(),
+            max_units: 0,
+            params: vec![program_id.into()],
+            require: Some(Require {
+                result: ResultAssertion::NumericEq(initial_size + balance_size),
+            }),
+        });
+
+        plan.add_step(Step {
+            endpoint: Endpoint::Execute,
+            method: "burn_from".into(),
+            params: vec![program_id.into(), alice_key],
+            max_units: 1000000,
+            require: None,
+        });
+
+        plan.add_step(Step {
+            endpoint: Endpoint::ReadOnly,
+            method: "state_size".into(),
+            max_units: 0,
+            params: vec![program_id.into()],
+            require: Some(Require {
+                result: ResultAssertion::NumericEq(initial_size),
+            }),
+        });
+
+        let plan_responses = simulator.run_plan(&plan).unwrap();
+
+        assert!(
+            plan_responses.iter().all(|resp| resp.error.is_none()),
+            "error: {:?}",
+            plan_responses
+                .iter()
+                .filter_map(|resp| resp.error.as_ref())
+                .next()
+        );
+    }
 }
@@ -2,9 +2,11 @@ extern crate proc_macro;
 
 use proc_macro::TokenStream;
 use quote::quote;
+use sha2::{Digest, Sha256};
 use syn::{
-    parse_macro_input, parse_str, spanned::Spanned, Fields, FnArg, Ident, ItemEnum, ItemFn, Pat,
-    PatType, Path, Type, Visibility,
+    ext::IdentExt, parse_macro_input, parse_str, punctuated::Punctuated, spanned::Spanned, Expr,
+    ExprLit, Fields, FnArg, Ident, ItemEnum, ItemFn, Lit, MetaNameValue, Pat, PatType, Path, Token,
+    Type, Visibility,
 };
 
 const CONEXT_TYPE: &str = "wasmlanche_sdk::Context";
@@ -14,9 +16,37 @@ const CONEXT_TYPE: &str = "wasmlanche_sdk::Context";
 /// The wrapper function will have the same name as the original function, but with "_guest" appended to it.
 /// The wrapper functions parameters will be converted to WASM supported types. When called, the wrapper function
 /// calls the original function by converting the parameters back to their intended types using .into().
+///
+/// `#[public(alias = "old_name")]` additionally exports the function under `old_name`,
+/// so callers using a previous name keep working after a rename. The option can be repeated.
+///
+/// Parameters of a signed integer type are rejected, since a negative amount can
+/// turn a subtraction from a balance into an addition. Annotate a parameter with
+/// `#[allow_signed]` when a signed value is really intended.
 #[proc_macro_attribute]
-pub fn public(_: TokenStream, item: TokenStream) -> TokenStream {
-    let input = parse_macro_input!(item as ItemFn);
+pub fn public(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let options =
+        parse_macro_input!(attr with Punctuated::<MetaNameValue, Token![,]>::parse_terminated);
+    let mut input = parse_macro_input!(item as ItemFn);
+
+    let aliases = match parse_aliases(options, &input.sig.ident) {
+        Ok(aliases) => aliases,
+        Err(errors) => return errors.to_compile_error().into(),
+    };
+
+    let signed_errors = input
+        .sig
+        .inputs
+        .iter_mut()
+        .skip(1)
+        .filter_map(|fn_arg| match fn_arg {
+            FnArg::Typed(pat_type) => check_signed_param(pat_type).err(),
+            FnArg::Receiver(_) => None,
+        })
+        .reduce(|mut errors, err| {
+            errors.combine(err);
+            errors
+        });
 
     let vis_err = if !matches!(input.vis, Visibility::Public(_)) {
         let err = syn::Error::new(
@@ -86,13 +116,15 @@ pub fn public(_: TokenStream, item: TokenStream) -> TokenStream {
             },
         });
 
-    let result = match (vis_err, first_arg_err) {
-        (None, None) => Ok(vec![]),
-        (Some(err), None) | (None, Some(err)) => Err(err),
-        (Some(mut vis_err), Some(first_arg_err)) => {
-            vis_err.combine(first_arg_err);
-            Err(vis_err)
-        }
+    let result = match [vis_err, first_arg_err, signed_errors]
+        .into_iter()
+        .flatten()
+        .reduce(|mut errors, err| {
+            errors.combine(err);
+            errors
+        }) {
+        None => Ok(vec![]),
+        Some(errors) => Err(errors),
     };
 
     let param_names_or_err = param_idents.fold(result, |result, param| match (result, param) {
@@ -127,6 +159,19 @@ pub fn public(_: TokenStream, item: TokenStream) -> TokenStream {
 
     // Extract the original function's return type. This must be a WASM supported type.
     let return_type = &input.sig.output;
+    let alias_wrappers = aliases
+        .iter()
+        .map(|alias| {
+            let param_types = param_types.clone();
+            quote! {
+                #[no_mangle]
+                pub extern "C" fn #alias(param_0: i64, #(#param_names: #param_types), *) #return_type {
+                    #new_name(param_0, #(#param_names),*)
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+
     let context_type: Path = parse_str(CONEXT_TYPE).unwrap();
     let output = quote! {
         // Need to include the original function in the output, so contract can call itself
@@ -138,21 +183,151 @@ pub fn public(_: TokenStream, item: TokenStream) -> TokenStream {
             };
             #name(param_0, #(#converted_params),*)
         }
+
+        #(#alias_wrappers)*
     };
 
     TokenStream::from(output)
 }
 
+/// Returns the exported names of the `alias` options passed to `#[public]`.
+/// An alias may not repeat the function name or another alias, since both
+/// would export the same symbol twice.
+fn parse_aliases(
+    options: Punctuated<MetaNameValue, Token![,]>,
+    name: &Ident,
+) -> Result<Vec<Ident>, syn::Error> {
+    let mut names = vec![name.unraw().to_string()];
+
+    options
+        .into_iter()
+        .map(|option| {
+            if !option.path.is_ident("alias") {
+                return Err(syn::Error::new(
+                    option.path.span(),
+                    "Unsupported `#[public]` option, expected `alias`.",
+                ));
+            }
+
+            match &option.value {
+                Expr::Lit(ExprLit {
+                    lit: Lit::Str(lit), ..
+                }) => {
+                    let alias = lit.parse::<Ident>().map_err(|_| {
+                        syn::Error::new(lit.span(), "The alias must be a valid function name.")
+                    })?;
+                    let alias = alias.unraw().to_string();
+                    if names.contains(&alias) {
+                        return Err(syn::Error::new(
+                            lit.span(),
+                            format!("The function is already exported as `{alias}`."),
+                        ));
+                    }
+
+                    let ident = Ident::new(&format!("{alias}_guest"), lit.span());
+                    names.push(alias);
+                    Ok(ident)
+                }
+                value => Err(syn::Error::new(
+                    value.span(),
+                    "The alias must be a string literal.",
+                )),
+            }
+        })
+        .collect()
+}
+
+/// Removes the `#[allow_signed]` attribute from a parameter and returns an error
+/// if the parameter is a signed integer without it.
+fn check_signed_param(pat_type: &mut PatType) -> Result<(), syn::Error> {
+    const SIGNED_INTEGERS: [&str; 6] = ["i8", "i16", "i32", "i64", "i128", "isize"];
+
+    let attr_count = pat_type.attrs.len();
+    pat_type
+        .attrs
+        .retain(|attr| !attr.path().is_ident("allow_signed"));
+    if pat_type.attrs.len() != attr_count {
+        return Ok(());
+    }
+
+    let Type::Path(type_path) = pat_type.ty.as_ref() else {
+        return Ok(());
+    };
+
+    // match on the last segment so qualified paths such as `core::primitive::i64` are caught too
+    match type_path.path.segments.last() {
+        Some(segment)
+            if segment.arguments.is_none()
+                && SIGNED_INTEGERS.contains(&segment.ident.to_string().as_str()) =>
+        {
+            let ident = &segment.ident;
+            Err(syn::Error::new(
+                pat_type.ty.span(),
+                format!("Parameters of functions with the `#[public]` attribute cannot be signed integers, use an unsigned type or annotate the parameter with `#[allow_signed]`. Found `{ident}`."),
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
 /// This macro assists in defining the schema for a program's state.  A user can
 /// simply define an enum with the desired state keys and the macro will
 /// generate the necessary code to convert the enum to a byte vector.
 /// The enum will automatically derive the Copy and Clone traits. As well as the
 /// repr(u8) attribute.
 ///
+/// Each key starts with the index of its variant as a prefix byte. The fields of
+/// a variant are appended after the prefix by iterating over their bytes.
+/// In raw mode integer fields are appended as fixed-width big-endian bytes, so
+/// `Metadata(u64)` becomes the prefix followed by 8 bytes on every platform
+/// and unsigned keys sort in numeric order. Signed integers keep their two's
+/// complement bytes, so negative values sort after positive ones. `usize` and
+/// `isize` fields are rejected since their width differs between platforms.
+///
+/// With `#[state_keys(hashed)]` the fields of every variant are instead borsh
+/// serialized and replaced by the first 16 bytes of their sha256 hash, so every
+/// variant with fields gives a fixed-size key of 17 bytes. Unit variants stay
+/// a single prefix byte. Two distinct keys collide with a probability of
+/// about `n^2 / 2^129` for `n` stored keys, which stays negligible for any
+/// realistic amount of state. Changing the mode of an enum changes every key
+/// it produces, so it must not be changed once a program is deployed.
+///
+/// The macro also generates a `LAYOUT_HASH`, the sha256 of a description of
+/// the key layout: the encoding mode followed by each variant's prefix, name
+/// and field types, e.g. `raw;0:Name();1:Balance(Address)`. It changes whenever
+/// an upgrade would change the meaning of existing keys. Store it at init and
+/// call the generated `check_layout` before touching state in a new version.
+///
+/// The generated `describe` returns the prefix byte and name of each variant,
+/// so tools can label the raw keys of a program.
+///
+/// A single variant can override the encoding of its fields with `#[key(raw)]`
+/// or `#[key(borsh)]`. `borsh` appends the borsh serialized fields after the
+/// prefix, with integers in little-endian, and also covers field types the
+/// raw encoding cannot iterate over, such as `bool`. Variants without the
+/// attribute use the mode of the enum. Overridden variants are marked in the
+/// layout description, e.g. `raw;0:Name();1:Paused(bool)@borsh`.
+///
 /// Note: The enum variants with named fields are not supported.
 #[proc_macro_attribute]
-pub fn state_keys(_attr: TokenStream, item: TokenStream) -> TokenStream {
+pub fn state_keys(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let options = parse_macro_input!(attr with Punctuated::<Ident, Token![,]>::parse_terminated);
     let mut item_enum = parse_macro_input!(item as ItemEnum);
+
+    let mut hashed = false;
+    for option in options {
+        if option == "hashed" {
+            hashed = true;
+        } else {
+            return syn::Error::new(
+                option.span(),
+                format!("Unsupported `#[state_keys]` option `{option}`, expected `hashed`."),
+            )
+            .to_compile_error()
+            .into();
+        }
+    }
+
     // add default attributes
     item_enum.attrs.push(syn::parse_quote! {
          #[derive(Clone, Copy, Debug)]
@@ -161,10 +336,39 @@ pub fn state_keys(_attr: TokenStream, item: TokenStream) -> TokenStream {
          #[repr(u8)]
     });
 
+    let overrides = match item_enum
+        .variants
+        .iter_mut()
+        .map(take_key_encoding)
+        .collect::<Result<Vec<_>, _>>()
+    {
+        Ok(overrides) => overrides,
+        Err(errors) => return errors.to_compile_error().into(),
+    };
+
     let name = &item_enum.ident;
     let variants = &item_enum.variants;
 
-    let to_vec_tokens = generate_to_vec(variants);
+    let default_encoding = if hashed {
+        KeyEncoding::Hashed
+    } else {
+        KeyEncoding::Raw
+    };
+    let encodings = overrides
+        .iter()
+        .map(|encoding| encoding.unwrap_or(default_encoding))
+        .collect::<Vec<_>>();
+
+    let to_vec_tokens = match generate_to_vec(variants, &encodings) {
+        Ok(tokens) => tokens,
+        Err(errors) => return errors.to_compile_error().into(),
+    };
+    let layout_hash = Sha256::digest(layout_description(variants, hashed, &overrides)).to_vec();
+    let descriptions = variants.iter().enumerate().map(|(idx, variant)| {
+        let index = idx as u8;
+        let variant_name = variant.ident.to_string();
+        quote! { (#index, #variant_name) }
+    });
     let gen = quote! {
         // generate the original enum definition with attributes
         #item_enum
@@ -176,6 +380,35 @@ pub fn state_keys(_attr: TokenStream, item: TokenStream) -> TokenStream {
                     #(#to_vec_tokens),*
                 }
             }
+
+            /// Returns the prefix byte and name of every variant, in declaration order.
+            pub fn describe() -> Vec<(u8, &'static str)> {
+                vec![#(#descriptions),*]
+            }
+
+            /// The sha256 hash of the key layout described by this enum.
+            pub const LAYOUT_HASH: [u8; 32] = [#(#layout_hash),*];
+
+            /// Returns the hash of the key layout described by this enum.
+            pub fn layout_hash() -> [u8; 32] {
+                Self::LAYOUT_HASH
+            }
+
+            /// Returns an error if the layout hash stored at `key` differs from [`Self::LAYOUT_HASH`].
+            pub fn check_layout(
+                state: &wasmlanche_sdk::state::State,
+                key: Self,
+            ) -> Result<(), wasmlanche_sdk::state::Error> {
+                let stored: [u8; 32] = state.get(key)?;
+                if stored == Self::LAYOUT_HASH {
+                    Ok(())
+                } else {
+                    Err(wasmlanche_sdk::state::Error::LayoutMismatch {
+                        stored,
+                        current: Self::LAYOUT_HASH,
+                    })
+                }
+            }
         }
 
         // Generate the Into<key> implementation needed to
@@ -190,33 +423,180 @@ pub fn state_keys(_attr: TokenStream, item: TokenStream) -> TokenStream {
     TokenStream::from(gen)
 }
 
+/// How the fields of a state key variant are appended to its prefix.
+#[derive(Clone, Copy)]
+enum KeyEncoding {
+    Raw,
+    Borsh,
+    Hashed,
+}
+
+impl KeyEncoding {
+    fn name(self) -> &'static str {
+        match self {
+            KeyEncoding::Raw => "raw",
+            KeyEncoding::Borsh => "borsh",
+            KeyEncoding::Hashed => "hashed",
+        }
+    }
+}
+
+/// Removes the `#[key(..)]` attribute from a variant and returns the encoding it selects, if any.
+fn take_key_encoding(variant: &mut syn::Variant) -> Result<Option<KeyEncoding>, syn::Error> {
+    let mut encoding = None;
+    let mut result = Ok(());
+
+    variant.attrs.retain(|attr| {
+        if !attr.path().is_ident("key") {
+            return true;
+        }
+
+        let parsed = attr.parse_args::<Ident>().and_then(|option| {
+            if encoding.is_some() {
+                return Err(syn::Error::new(
+                    attr.span(),
+                    "A state key variant can only have one `#[key]` attribute.",
+                ));
+            }
+
+            match option.to_string().as_str() {
+                "raw" => Ok(KeyEncoding::Raw),
+                "borsh" => Ok(KeyEncoding::Borsh),
+                _ => Err(syn::Error::new(
+                    option.span(),
+                    format!("Unsupported `#[key]` encoding `{option}`, expected `raw` or `borsh`."),
+                )),
+            }
+        });
+
+        match parsed {
+            Ok(parsed) => encoding = Some(parsed),
+            Err(err) => match &mut result {
+                Ok(()) => result = Err(err),
+                Err(errors) => errors.combine(err),
+            },
+        }
+
+        false
+    });
+
+    result.map(|()| encoding)
+}
+
+/// Returns the canonical description of the key layout that `LAYOUT_HASH` is computed from.
+fn layout_description(
+    variants: &Punctuated<syn::Variant, Token![,]>,
+    hashed: bool,
+    overrides: &[Option<KeyEncoding>],
+) -> String {
+    let mode = if hashed { "hashed" } else { "raw" };
+    let variants = variants
+        .iter()
+        .zip(overrides)
+        .enumerate()
+        .map(|(idx, (variant, encoding))| {
+            let fields = variant
+                .fields
+                .iter()
+                .map(|field| {
+                    let ty = &field.ty;
+                    quote!(#ty).to_string().replace(' ', "")
+                })
+                .collect::<Vec<_>>()
+                .join(",");
+
+            match encoding {
+                Some(encoding) => format!("{idx}:{}({fields})@{}", variant.ident, encoding.name()),
+                None => format!("{idx}:{}({fields})", variant.ident),
+            }
+        });
+
+    std::iter::once(mode.to_string())
+        .chain(variants)
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
 fn generate_to_vec(
-    variants: &syn::punctuated::Punctuated<syn::Variant, syn::Token![,]>,
-) -> Vec<proc_macro2::TokenStream> {
+    variants: &Punctuated<syn::Variant, Token![,]>,
+    encodings: &[KeyEncoding],
+) -> Result<Vec<proc_macro2::TokenStream>, syn::Error> {
     variants
         .iter()
+        .zip(encodings)
         .enumerate()
-        .map(|(idx, variant)| {
+        .map(|(idx, (variant, encoding))| {
             let variant_ident = &variant.ident;
             let index = idx as u8;
             match &variant.fields {
                 // ex: Point(f64, f64)
-                Fields::Unnamed(_) => quote! {
-                    Self::#variant_ident(a) => std::iter::once(#index).chain(a.into_iter()).collect()
-                },
+                Fields::Unnamed(fields) => {
+                    let types = fields.unnamed.iter().map(|field| &field.ty);
+                    let fields = (0..fields.unnamed.len())
+                        .map(|i| Ident::new(&format!("field_{i}"), variant_ident.span()))
+                        .collect::<Vec<_>>();
+
+                    match encoding {
+                        KeyEncoding::Hashed => {
+                            return Ok(quote! {
+                                Self::#variant_ident(#(#fields),*) => wasmlanche_sdk::state::hashed_key(#index, &(#(#fields,)*))
+                            });
+                        }
+                        KeyEncoding::Borsh => {
+                            return Ok(quote! {
+                                Self::#variant_ident(#(#fields),*) => wasmlanche_sdk::state::borsh_key(#index, &(#(#fields,)*))
+                            });
+                        }
+                        KeyEncoding::Raw => {}
+                    }
+
+                    let encoded_fields = fields
+                        .iter()
+                        .zip(types)
+                        .map(|(field, ty)| raw_field_bytes(field, ty))
+                        .collect::<Result<Vec<_>, _>>()?;
+
+                    Ok(quote! {
+                        Self::#variant_ident(#(#fields),*) => std::iter::once(#index)#(.chain(#encoded_fields))*.collect()
+                    })
+                }
                 // ex: Point
-                Fields::Unit => quote! {
+                Fields::Unit => Ok(quote! {
                     Self::#variant_ident => vec![#index]
-                },
+                }),
                 // ex: Point { x: f64, y: f64 }
-                Fields::Named(_) => quote! {
+                Fields::Named(_) => Ok(quote! {
                     Self::#variant_ident { .. } => panic!("named enum fields are not supported"),
-                },
+                }),
             }
         })
         .collect()
 }
 
+/// Returns an iterator expression over the bytes of a key field. Integers are
+/// encoded as fixed-width big-endian bytes, two's complement for signed ones,
+/// anything else is iterated over.
+fn raw_field_bytes(field: &Ident, ty: &Type) -> Result<proc_macro2::TokenStream, syn::Error> {
+    const FIXED_WIDTH_INTEGERS: [&str; 10] = [
+        "u8", "u16", "u32", "u64", "u128", "i8", "i16", "i32", "i64", "i128",
+    ];
+
+    let Type::Path(type_path) = ty else {
+        return Ok(quote! { #field.into_iter() });
+    };
+
+    match type_path.path.get_ident().map(ToString::to_string) {
+        Some(ident) if FIXED_WIDTH_INTEGERS.contains(&ident.as_str()) => {
+            Ok(quote! { #field.to_be_bytes() })
+        }
+        Some(ident) if ident == "usize" || ident == "isize" => Err(syn::Error::new(
+            ty.span(),
+            format!("`{ident}` state key fields are platform dependent, use a fixed-width integer instead."),
+        )),
+        _ => Ok(quote! { #field.into_iter() }),
+    }
+}
+
 /// Returns whether the type_path represents a Program type.
 fn is_context(type_path: &std::boxed::Box<Type>) -> bool {
     if let Type::Path(type_path) = type_path.as_ref() {
@@ -0,0 +1,20 @@
+use sdk_macros::public;
+#[allow(unused_imports)]
+use wasmlanche_sdk::Context;
+
+#[public(rename = "other")]
+pub fn unknown_option(_: Context) {}
+
+#[public(alias = 1)]
+pub fn alias_not_a_string(_: Context) {}
+
+#[public(alias = "not a name")]
+pub fn alias_not_a_name(_: Context) {}
+
+#[public(alias = "same_name")]
+pub fn same_name(_: Context) {}
+
+#[public(alias = "twice", alias = "r#twice")]
+pub fn alias_repeated(_: Context) {}
+
+fn main() {}
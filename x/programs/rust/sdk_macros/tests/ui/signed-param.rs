@@ -0,0 +1,15 @@
+use sdk_macros::public;
+#[allow(unused_imports)]
+use wasmlanche_sdk::Context;
+
+#[public]
+pub fn test(_: Context, amount: i64, #[allow_signed] delta: i32) -> bool {
+    amount > 0 && delta > 0
+}
+
+#[public]
+pub fn qualified(_: Context, amount: core::primitive::i64, delta: std::primitive::i32) -> bool {
+    amount > 0 && delta > 0
+}
+
+fn main() {}
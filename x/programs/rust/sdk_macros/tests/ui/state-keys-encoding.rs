@@ -0,0 +1,9 @@
+use sdk_macros::state_keys;
+
+#[state_keys]
+enum StateKey {
+    #[key(compact)]
+    Name,
+}
+
+fn main() {}
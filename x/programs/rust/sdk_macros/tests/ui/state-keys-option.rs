@@ -0,0 +1,8 @@
+use sdk_macros::state_keys;
+
+#[state_keys(compact)]
+enum StateKey {
+    Name,
+}
+
+fn main() {}
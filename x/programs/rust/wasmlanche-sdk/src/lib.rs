@@ -1,6 +1,11 @@
 #![deny(clippy::pedantic)]
 
+pub mod merkle;
+pub mod metadata;
 pub mod params;
+pub mod prelude;
+pub mod rand;
+pub mod signing;
 pub mod state;
 pub mod types;
 
@@ -10,7 +15,7 @@ mod program;
 pub use self::{
     memory::{from_host_ptr, HostPtr},
     params::{serialize_param, Params},
-    program::Program,
+    program::{CallBuilder, Program},
 };
 
 #[cfg(feature = "build")]
@@ -0,0 +1,263 @@
+//! An append-only merkle accumulator that can be kept in program state.
+//!
+//! Only the right-most filled subtree at each level is kept, so inserting a
+//! leaf costs `DEPTH` hashes and the tree occupies `DEPTH + 1` hashes of
+//! storage regardless of how many leaves were inserted. Proofs are built
+//! off-chain from the inserted leaves and checked with [`verify_proof`].
+//!
+//! Nodes are `sha256(left || right)` and empty leaves are 32 zero bytes.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use sha2::{Digest, Sha256};
+
+/// A 32 byte node of the tree.
+pub type Hash = [u8; 32];
+
+/// The depth used by most programs, allowing for `2^20` leaves.
+pub const DEFAULT_DEPTH: usize = 20;
+
+#[derive(Clone, Debug, thiserror::Error, PartialEq, Eq)]
+pub enum Error {
+    #[error("the tree cannot hold more leaves")]
+    TreeFull,
+}
+
+/// An incremental merkle tree of fixed `DEPTH`. Store it as a single value
+/// under one of the program's state keys.
+#[derive(Clone, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct IncrementalTree<const DEPTH: usize = DEFAULT_DEPTH> {
+    next_index: u64,
+    filled_subtrees: [Hash; DEPTH],
+    root: Hash,
+}
+
+impl<const DEPTH: usize> Default for IncrementalTree<DEPTH> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const DEPTH: usize> IncrementalTree<DEPTH> {
+    /// Returns an empty tree.
+    #[must_use]
+    pub fn new() -> Self {
+        let mut root = [0; 32];
+        for _ in 0..DEPTH {
+            root = hash_pair(&root, &root);
+        }
+
+        Self {
+            next_index: 0,
+            filled_subtrees: [[0; 32]; DEPTH],
+            root,
+        }
+    }
+
+    /// Appends `leaf` to the tree and returns its index.
+    /// # Errors
+    /// Returns [`Error::TreeFull`] if the tree already holds `2^DEPTH` leaves.
+    pub fn insert(&mut self, leaf: Hash) -> Result<u64, Error> {
+        let index = self.next_index;
+        let is_full = u32::try_from(DEPTH)
+            .ok()
+            .and_then(|depth| index.checked_shr(depth))
+            .is_some_and(|rest| rest > 0);
+        if is_full {
+            return Err(Error::TreeFull);
+        }
+
+        let mut node = leaf;
+        let mut zero = [0; 32];
+        let mut position = index;
+
+        for subtree in &mut self.filled_subtrees {
+            node = if position & 1 == 0 {
+                *subtree = node;
+                hash_pair(&node, &zero)
+            } else {
+                hash_pair(subtree, &node)
+            };
+            zero = hash_pair(&zero, &zero);
+            position >>= 1;
+        }
+
+        self.root = node;
+        self.next_index += 1;
+
+        Ok(index)
+    }
+
+    /// Returns the current root of the tree.
+    #[must_use]
+    pub fn root(&self) -> Hash {
+        self.root
+    }
+
+    /// Returns the number of leaves inserted so far.
+    #[must_use]
+    pub fn len(&self) -> u64 {
+        self.next_index
+    }
+
+    /// Returns whether no leaves were inserted yet.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.next_index == 0
+    }
+}
+
+/// Returns whether `leaf` is at `index` in the tree with the given `root`.
+/// `siblings` are the sibling nodes from the leaf level up to just below the root.
+#[must_use]
+pub fn verify_proof<const DEPTH: usize>(
+    leaf: &Hash,
+    index: u64,
+    siblings: &[Hash; DEPTH],
+    root: &Hash,
+) -> bool {
+    let mut node = *leaf;
+    let mut position = index;
+
+    for sibling in siblings {
+        node = if position & 1 == 0 {
+            hash_pair(&node, sibling)
+        } else {
+            hash_pair(sibling, &node)
+        };
+        position >>= 1;
+    }
+
+    position == 0 && node == *root
+}
+
+fn hash_pair(left: &Hash, right: &Hash) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEPTH: usize = 4;
+
+    /// Builds every level of the tree from the full list of leaves.
+    fn reference_levels(leaves: &[Hash]) -> Vec<Vec<Hash>> {
+        let mut zero = [0; 32];
+        let mut levels = vec![leaves.to_vec()];
+
+        for _ in 0..DEPTH {
+            let mut nodes = levels.last().unwrap().clone();
+            while nodes.is_empty() || nodes.len() % 2 == 1 {
+                nodes.push(zero);
+            }
+            let parents = nodes
+                .chunks(2)
+                .map(|pair| {
+                    let mut hasher = Sha256::new();
+                    hasher.update(pair[0]);
+                    hasher.update(pair[1]);
+                    hasher.finalize().into()
+                })
+                .collect::<Vec<Hash>>();
+            levels.push(parents);
+
+            let mut hasher = Sha256::new();
+            hasher.update(zero);
+            hasher.update(zero);
+            zero = hasher.finalize().into();
+        }
+
+        levels
+    }
+
+    fn reference_root(leaves: &[Hash]) -> Hash {
+        reference_levels(leaves).last().unwrap()[0]
+    }
+
+    fn leaf(i: u8) -> Hash {
+        [i + 1; 32]
+    }
+
+    #[test]
+    fn roots_match_reference() {
+        for count in [0, 1, 2, 3, 8, 11, 16] {
+            let leaves = (0..count).map(leaf).collect::<Vec<_>>();
+            let mut tree = IncrementalTree::<DEPTH>::new();
+
+            for (i, leaf) in leaves.iter().enumerate() {
+                assert_eq!(tree.insert(*leaf), Ok(i as u64));
+            }
+
+            assert_eq!(tree.len(), u64::from(count));
+            assert_eq!(tree.root(), reference_root(&leaves), "count: {count}");
+        }
+    }
+
+    #[test]
+    fn empty_root_is_zero_subtree() {
+        let mut zero = [0; 32];
+        for _ in 0..DEPTH {
+            zero = hash_pair(&zero, &zero);
+        }
+
+        assert!(IncrementalTree::<DEPTH>::new().is_empty());
+        assert_eq!(IncrementalTree::<DEPTH>::new().root(), zero);
+    }
+
+    #[test]
+    fn full_tree_rejects_insert() {
+        let mut tree = IncrementalTree::<DEPTH>::new();
+        for i in 0..16 {
+            tree.insert(leaf(i)).unwrap();
+        }
+
+        assert_eq!(tree.insert(leaf(16)), Err(Error::TreeFull));
+        assert_eq!(tree.len(), 16);
+    }
+
+    #[test]
+    fn proofs_verify_against_root() {
+        let leaves = (0..5).map(leaf).collect::<Vec<_>>();
+        let mut tree = IncrementalTree::<DEPTH>::new();
+        for leaf in &leaves {
+            tree.insert(*leaf).unwrap();
+        }
+
+        let levels = reference_levels(&leaves);
+        let mut zero = [0; 32];
+        let mut zeros = [[0; 32]; DEPTH];
+        for z in &mut zeros {
+            *z = zero;
+            zero = hash_pair(&zero, &zero);
+        }
+
+        for (index, leaf) in leaves.iter().enumerate() {
+            let siblings: [Hash; DEPTH] = std::array::from_fn(|level| {
+                let position = (index >> level) ^ 1;
+                levels[level].get(position).copied().unwrap_or(zeros[level])
+            });
+
+            assert!(verify_proof(leaf, index as u64, &siblings, &tree.root()));
+            assert!(!verify_proof(
+                leaf,
+                index as u64 + 1,
+                &siblings,
+                &tree.root()
+            ));
+        }
+    }
+
+    #[test]
+    fn borsh_round_trip() {
+        let mut tree = IncrementalTree::<DEPTH>::new();
+        tree.insert(leaf(0)).unwrap();
+
+        let bytes = borsh::to_vec(&tree).unwrap();
+        let decoded: IncrementalTree<DEPTH> = borsh::from_slice(&bytes).unwrap();
+
+        assert_eq!(decoded, tree);
+    }
+}
@@ -0,0 +1,84 @@
+//! Checks on collection metadata that programs run at init.
+
+#[derive(Clone, Debug, thiserror::Error, PartialEq, Eq)]
+pub enum MetadataError {
+    #[error("trait weights sum to {allocated}, more than the supply of {supply}")]
+    OverAllocated { allocated: u64, supply: u64 },
+
+    #[error("trait weights sum to {allocated}, less than the supply of {supply}")]
+    UnderAllocated { allocated: u64, supply: u64 },
+}
+
+/// Checks that the weights of `traits`, each the number of tokens that receive
+/// the named trait, add up to exactly `total_supply`, so every token of a
+/// generative collection gets one trait and no trait is left over.
+/// # Errors
+/// Returns [`MetadataError::OverAllocated`] or [`MetadataError::UnderAllocated`]
+/// if the weights sum to more or less than `total_supply`. A sum that does
+/// not fit in a `u64` is over-allocated and reported as `u64::MAX`.
+pub fn validate_distribution(
+    traits: &[(String, u32)],
+    total_supply: u64,
+) -> Result<(), MetadataError> {
+    let Some(allocated) = traits.iter().try_fold(0_u64, |sum, (_, weight)| {
+        sum.checked_add(u64::from(*weight))
+    }) else {
+        return Err(MetadataError::OverAllocated {
+            allocated: u64::MAX,
+            supply: total_supply,
+        });
+    };
+
+    match allocated.cmp(&total_supply) {
+        std::cmp::Ordering::Equal => Ok(()),
+        std::cmp::Ordering::Greater => Err(MetadataError::OverAllocated {
+            allocated,
+            supply: total_supply,
+        }),
+        std::cmp::Ordering::Less => Err(MetadataError::UnderAllocated {
+            allocated,
+            supply: total_supply,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn traits(weights: &[u32]) -> Vec<(String, u32)> {
+        weights
+            .iter()
+            .enumerate()
+            .map(|(i, weight)| (format!("trait {i}"), *weight))
+            .collect()
+    }
+
+    #[test]
+    fn matching_distribution_is_valid() {
+        assert_eq!(validate_distribution(&traits(&[5, 3, 2]), 10), Ok(()));
+        assert_eq!(validate_distribution(&[], 0), Ok(()));
+    }
+
+    #[test]
+    fn over_allocated_distribution_is_rejected() {
+        assert_eq!(
+            validate_distribution(&traits(&[5, 3, 3]), 10),
+            Err(MetadataError::OverAllocated {
+                allocated: 11,
+                supply: 10
+            })
+        );
+    }
+
+    #[test]
+    fn under_allocated_distribution_is_rejected() {
+        assert_eq!(
+            validate_distribution(&traits(&[5, 3, 1]), 10),
+            Err(MetadataError::UnderAllocated {
+                allocated: 9,
+                supply: 10
+            })
+        );
+    }
+}
@@ -0,0 +1,8 @@
+//! Re-exports of the items most programs need, so they can be brought into
+//! scope with a single import.
+//!
+//! ```
+//! use wasmlanche_sdk::prelude::*;
+//! ```
+
+pub use crate::{public, state_keys, types::Address, Context, Program};
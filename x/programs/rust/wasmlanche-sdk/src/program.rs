@@ -1,6 +1,12 @@
 use borsh::{BorshDeserialize, BorshSerialize};
 
-use crate::{memory::to_host_ptr, state::Error as StateError, state::State, Params};
+use crate::{
+    memory::to_host_ptr,
+    params::{serialize_param, Param},
+    state::Error as StateError,
+    state::State,
+    Error, Params,
+};
 
 /// Represents the current Program in the context of the caller. Or an external
 /// program that is being invoked.
@@ -48,6 +54,54 @@ impl Program {
 
         Ok(unsafe { _call_program(target, function, args, max_units) })
     }
+
+    /// Starts building a call to the function `function_name` of this program
+    /// that may consume at most `max_units`. Arguments are serialized in the
+    /// order they are added, e.g. `target.call("inc", 1000).arg(&to).arg(&amount).invoke()`.
+    pub fn call<'a>(&self, function_name: &'a str, max_units: i64) -> CallBuilder<'a> {
+        CallBuilder {
+            target: *self,
+            function_name,
+            args: Ok(Vec::new()),
+            max_units,
+        }
+    }
+}
+
+/// Builds a call to a function of another program, see [`Program::call`].
+#[must_use]
+pub struct CallBuilder<'a> {
+    target: Program,
+    function_name: &'a str,
+    args: Result<Vec<Param>, Error>,
+    max_units: i64,
+}
+
+impl CallBuilder<'_> {
+    /// Appends an argument to the call.
+    pub fn arg<T>(mut self, arg: &T) -> Self
+    where
+        T: BorshSerialize,
+    {
+        if let Ok(args) = &mut self.args {
+            match serialize_param(arg) {
+                Ok(param) => args.push(param),
+                Err(err) => self.args = Err(err),
+            }
+        }
+
+        self
+    }
+
+    /// Calls the function and returns its result.
+    /// # Errors
+    /// Returns an [`Error`] if an argument could not be serialized or the call fails.
+    pub fn invoke(self) -> Result<i64, Error> {
+        let params = self.args?.into_iter().collect();
+        Ok(self
+            .target
+            .call_function(self.function_name, params, self.max_units)?)
+    }
 }
 
 #[link(wasm_import_module = "program")]
@@ -0,0 +1,86 @@
+//! Deterministic randomness derived from a seed, such as a committed reveal seed.
+//!
+//! The `i`-th draw from a seed is the first 8 bytes of `sha256(seed || i)`,
+//! read as a big-endian `u64` with `i` encoded as 8 big-endian bytes. Anyone
+//! holding the seed can reproduce every result off-chain.
+
+use sha2::{Digest, Sha256};
+
+/// Returns a permutation of `0..n` shuffled with Fisher-Yates from `seed`.
+/// The same seed and `n` always produce the same permutation.
+/// # Panics
+/// Panics if `n` does not fit in a `usize`.
+#[must_use]
+pub fn shuffle_indices(seed: [u8; 32], n: u64) -> Vec<u64> {
+    let len = usize::try_from(n).expect("n out of range");
+    let mut indices = (0..n).collect::<Vec<_>>();
+    let mut draws = Draws { seed, counter: 0 };
+
+    for i in (1..len).rev() {
+        let bound = u64::try_from(i + 1).expect("index out of range");
+        let j = usize::try_from(draws.below(bound)).expect("index out of range");
+        indices.swap(i, j);
+    }
+
+    indices
+}
+
+struct Draws {
+    seed: [u8; 32],
+    counter: u64,
+}
+
+impl Draws {
+    fn next(&mut self) -> u64 {
+        let mut hasher = Sha256::new();
+        hasher.update(self.seed);
+        hasher.update(self.counter.to_be_bytes());
+        self.counter += 1;
+
+        let hash = hasher.finalize();
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(&hash[..8]);
+        u64::from_be_bytes(bytes)
+    }
+
+    /// Returns a uniform value in `0..bound`, rejecting the draws that would
+    /// bias the lower values.
+    fn below(&mut self, bound: u64) -> u64 {
+        let limit = u64::MAX - u64::MAX % bound;
+        loop {
+            let draw = self.next();
+            if draw < limit {
+                return draw % bound;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shuffle_is_a_permutation() {
+        for n in [0, 1, 2, 10, 257] {
+            let mut indices = shuffle_indices([3; 32], n);
+            indices.sort_unstable();
+
+            assert_eq!(indices, (0..n).collect::<Vec<_>>(), "n: {n}");
+        }
+    }
+
+    #[test]
+    fn shuffle_is_reproducible() {
+        let seed = [9; 32];
+
+        assert_eq!(shuffle_indices(seed, 100), shuffle_indices(seed, 100));
+        assert_ne!(shuffle_indices(seed, 100), shuffle_indices([8; 32], 100));
+    }
+
+    // This ordering must never change, otherwise past reveals cannot be reproduced.
+    #[test]
+    fn shuffle_is_pinned() {
+        assert_eq!(shuffle_indices([0; 32], 8), [7, 3, 0, 6, 1, 2, 5, 4]);
+    }
+}
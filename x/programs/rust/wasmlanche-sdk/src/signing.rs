@@ -0,0 +1,120 @@
+//! Canonical encoding of messages that are signed off-chain and verified by a
+//! program. Building the signed bytes with [`Payload`] on both sides removes
+//! any ambiguity about how the fields are laid out.
+//!
+//! The layout is:
+//! - the domain as a big-endian `u32` length followed by its UTF-8 bytes
+//! - the 32 byte chain id
+//! - the 32 byte program id
+//! - each field in the order it was appended
+
+use crate::{program::Program, types::Address};
+
+/// A deterministic byte string to be signed, bound to a domain, a chain and a program.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Payload(Vec<u8>);
+
+impl Payload {
+    /// The length of a chain id.
+    pub const CHAIN_ID_LEN: usize = 32;
+
+    /// Starts a new payload for `domain` on the given chain and program.
+    /// # Panics
+    /// Panics if the byte-length of `domain` exceeds [`u32::MAX`].
+    #[must_use]
+    pub fn new(
+        domain: &str,
+        chain_id: &[u8; Self::CHAIN_ID_LEN],
+        program_id: &[u8; Program::LEN],
+    ) -> Self {
+        Self(Vec::new())
+            .field_str(domain)
+            .field_bytes(chain_id)
+            .field_bytes(program_id)
+    }
+
+    /// Appends a `u64` as 8 big-endian bytes.
+    #[must_use]
+    pub fn field_u64(self, value: u64) -> Self {
+        self.field_bytes(&value.to_be_bytes())
+    }
+
+    /// Appends the 32 bytes of an [`Address`].
+    #[must_use]
+    pub fn field_address(self, address: &Address) -> Self {
+        self.field_bytes(address.as_ref())
+    }
+
+    /// Appends a string as a big-endian `u32` length followed by its UTF-8 bytes.
+    /// # Panics
+    /// Panics if the byte-length of `value` exceeds [`u32::MAX`].
+    #[must_use]
+    pub fn field_str(self, value: &str) -> Self {
+        let len = u32::try_from(value.len()).expect("string length out of range");
+        self.field_bytes(&len.to_be_bytes())
+            .field_bytes(value.as_bytes())
+    }
+
+    /// Returns the bytes to sign or verify.
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Consumes the payload and returns the bytes to sign or verify.
+    #[must_use]
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.0
+    }
+
+    fn field_bytes(mut self, bytes: &[u8]) -> Self {
+        self.0.extend_from_slice(bytes);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn header_layout() {
+        let payload = Payload::new("permit", &[1; 32], &[2; 32]);
+
+        let mut expected = vec![0, 0, 0, 6];
+        expected.extend_from_slice(b"permit");
+        expected.extend_from_slice(&[1; 32]);
+        expected.extend_from_slice(&[2; 32]);
+
+        assert_eq!(payload.as_bytes(), expected.as_slice());
+    }
+
+    #[test]
+    fn field_layout() {
+        let payload = Payload::new("", &[0; 32], &[0; 32])
+            .field_u64(0x0102_0304_0506_0708)
+            .field_address(&Address::new([9; 32]))
+            .field_str("hi");
+
+        let bytes = payload.into_bytes();
+        let fields = &bytes[4 + 32 + 32..];
+
+        let mut expected = vec![1, 2, 3, 4, 5, 6, 7, 8];
+        expected.extend_from_slice(&[9; 32]);
+        expected.extend_from_slice(&[0, 0, 0, 2, b'h', b'i']);
+
+        assert_eq!(fields, expected.as_slice());
+    }
+
+    #[test]
+    fn field_order_matters() {
+        let a = Payload::new("d", &[0; 32], &[0; 32])
+            .field_u64(1)
+            .field_u64(2);
+        let b = Payload::new("d", &[0; 32], &[0; 32])
+            .field_u64(2)
+            .field_u64(1);
+
+        assert_ne!(a, b);
+    }
+}
@@ -1,6 +1,13 @@
-use crate::{memory::from_host_ptr, program::Program};
+use crate::{
+    memory::{from_host_ptr, into_bytes, split_host_ptr},
+    program::Program,
+};
 use borsh::{BorshDeserialize, BorshSerialize};
-use std::ops::Deref;
+use sha2::{Digest, Sha256};
+use std::{marker::PhantomData, ops::Deref};
+
+/// The number of bytes of the payload hash kept by [`hashed_key`].
+pub const HASHED_KEY_LEN: usize = 16;
 
 #[derive(Clone, thiserror::Error, Debug)]
 pub enum Error {
@@ -33,6 +40,9 @@ pub enum Error {
 
     #[error("failed to delete from host storage")]
     Delete,
+
+    #[error("state layout mismatch: stored {stored:?}, current {current:?}")]
+    LayoutMismatch { stored: [u8; 32], current: [u8; 32] },
 }
 
 pub struct State {
@@ -82,6 +92,84 @@ impl State {
         unsafe { from_host_ptr(val_ptr) }
     }
 
+    /// Get the undecoded bytes of a value of type `T` from the host's storage.
+    /// Use this when the value only needs to be checked for existence or
+    /// forwarded, and [`Raw::decode`] it only when needed.
+    ///
+    /// # Errors
+    /// Returns an [Error] if the key cannot be serialized or if
+    /// the host fails to read the key and value.
+    pub fn get_raw_typed<T, K>(&self, key: K) -> Result<Raw<T>, Error>
+    where
+        K: Into<Key>,
+        T: BorshDeserialize,
+    {
+        let val_ptr = unsafe { host::get_bytes(&self.program, &key.into())? };
+        if val_ptr < 0 {
+            return Err(Error::Read);
+        }
+
+        Ok(Raw::new(into_bytes(val_ptr)))
+    }
+
+    /// Decode only the `len` bytes at `offset` of the value stored at `key` as a `T`,
+    /// such as a single field of a larger borsh serialized struct. The rest of
+    /// the value is neither copied nor decoded.
+    ///
+    /// Returns `None` if nothing is stored at `key`.
+    /// # Errors
+    /// Returns an [`Error::InvalidByteLength`] with the length of the stored
+    /// value if the range does not fit in it, an [`Error::Deserialization`] if
+    /// the range is not a valid `T`, or an [Error] if the host fails to read the key.
+    pub fn peek_field<T, K>(&self, key: K, offset: usize, len: usize) -> Result<Option<T>, Error>
+    where
+        K: Into<Key>,
+        T: BorshDeserialize,
+    {
+        let val_ptr = unsafe { host::get_bytes(&self.program, &key.into())? };
+        if val_ptr < 0 {
+            return Ok(None);
+        }
+
+        let (ptr, value_len) = split_host_ptr(val_ptr);
+        let value = unsafe { std::slice::from_raw_parts(ptr as *const u8, value_len) };
+        let field = offset
+            .checked_add(len)
+            .and_then(|end| value.get(offset..end))
+            .ok_or(Error::InvalidByteLength(value_len))?;
+
+        borsh::from_slice(field)
+            .map(Some)
+            .map_err(|_| Error::Deserialization)
+    }
+
+    /// Run `f` only if `key` was never recorded before, and record it once `f`
+    /// succeeds. Use a key identifying the operation, such as an idempotency
+    /// key sent along with a transaction that may be retried.
+    ///
+    /// Returns the result of `f` the first time it succeeds and `None` on any
+    /// later call with the same key. If `f` fails the key is not recorded, so
+    /// a retry runs `f` again.
+    /// # Errors
+    /// Returns the error of `f`, or an [Error] if the key cannot be serialized
+    /// or if the host fails to read or record the key.
+    pub fn once<K, R, E, F>(&self, key: K, f: F) -> Result<Option<R>, E>
+    where
+        K: Into<Key>,
+        E: From<Error>,
+        F: FnOnce() -> Result<R, E>,
+    {
+        let key = key.into();
+        if unsafe { host::get_bytes(&self.program, &key)? } >= 0 {
+            return Ok(None);
+        }
+
+        let result = f()?;
+        unsafe { host::put_bytes(&self.program, &key, &true)? };
+
+        Ok(Some(result))
+    }
+
     /// Delete a value from the hosts's storage.
     /// # Errors
     /// Returns an [Error] if the key cannot be serialized
@@ -114,6 +202,79 @@ impl Key {
     }
 }
 
+/// The borsh serialized bytes of a stored value of type `T`, decoded on demand.
+/// Serializing a `Raw<T>` writes the bytes unchanged, so it can be stored or
+/// passed on without a decode and re-encode.
+pub struct Raw<T> {
+    bytes: Vec<u8>,
+    value: PhantomData<T>,
+}
+
+impl<T> Raw<T>
+where
+    T: BorshDeserialize,
+{
+    pub(crate) fn new(bytes: Vec<u8>) -> Self {
+        Self {
+            bytes,
+            value: PhantomData,
+        }
+    }
+
+    /// Returns the serialized bytes of the value.
+    #[must_use]
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Deserializes the value.
+    /// # Errors
+    /// Returns an [`Error::Deserialization`] if the bytes are not a valid `T`.
+    pub fn decode(&self) -> Result<T, Error> {
+        borsh::from_slice(&self.bytes).map_err(|_| Error::Deserialization)
+    }
+}
+
+impl<T> BorshSerialize for Raw<T> {
+    fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        writer.write_all(&self.bytes)
+    }
+}
+
+/// Returns `prefix` followed by the first [`HASHED_KEY_LEN`] bytes of the
+/// sha256 hash of the borsh serialized `payload`. This is the encoding used
+/// by `#[state_keys(hashed)]`.
+/// # Panics
+/// Panics if `payload` cannot be serialized.
+#[must_use]
+pub fn hashed_key<T>(prefix: u8, payload: &T) -> Vec<u8>
+where
+    T: BorshSerialize,
+{
+    let payload = borsh::to_vec(payload).expect("failed to serialize key payload");
+    let hash = Sha256::digest(payload);
+
+    std::iter::once(prefix)
+        .chain(hash.into_iter().take(HASHED_KEY_LEN))
+        .collect()
+}
+
+/// Returns `prefix` followed by the borsh serialized `payload`. This is the
+/// encoding used by `#[state_keys]` variants marked with `#[key(borsh)]`.
+/// # Panics
+/// Panics if `payload` cannot be serialized.
+#[must_use]
+pub fn borsh_key<T>(prefix: u8, payload: &T) -> Vec<u8>
+where
+    T: BorshSerialize,
+{
+    let mut key = vec![prefix];
+    payload
+        .serialize(&mut key)
+        .expect("failed to serialize key payload");
+    key
+}
+
 mod host {
     use super::{BorshSerialize, Key, Program};
     use crate::{memory::to_host_ptr, state::Error};
@@ -165,3 +326,87 @@ mod host {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raw_decodes_on_demand() {
+        let value = (7_u64, String::from("metadata"));
+        let bytes = borsh::to_vec(&value).unwrap();
+        let raw = Raw::<(u64, String)>::new(bytes.clone());
+
+        assert_eq!(raw.bytes(), bytes.as_slice());
+        assert_eq!(raw.decode().unwrap(), value);
+        // re-serializing forwards the stored bytes unchanged
+        assert_eq!(borsh::to_vec(&raw).unwrap(), bytes);
+    }
+
+    #[test]
+    fn raw_reports_invalid_bytes() {
+        let raw = Raw::<u64>::new(vec![1, 2, 3]);
+
+        assert!(matches!(raw.decode(), Err(Error::Deserialization)));
+    }
+
+    #[test]
+    fn error_messages() {
+        let cases = [
+            (
+                Error::Other(String::from("oops")),
+                String::from("an unclassified error has occurred: oops"),
+            ),
+            (Error::InvalidBytes, String::from("invalid byte format")),
+            (
+                Error::InvalidByteLength(3),
+                String::from("invalid byte length: 3"),
+            ),
+            (Error::InvalidTag(7), String::from("invalid tag: 7")),
+            (
+                Error::Write,
+                String::from("failed to write to host storage"),
+            ),
+            (
+                Error::Read,
+                String::from("failed to read from host storage"),
+            ),
+            (
+                Error::Serialization,
+                String::from("failed to serialize bytes"),
+            ),
+            (
+                Error::Deserialization,
+                String::from("failed to deserialize bytes"),
+            ),
+            (
+                Error::IntegerConversion,
+                String::from("failed to convert integer"),
+            ),
+            (
+                Error::Delete,
+                String::from("failed to delete from host storage"),
+            ),
+            (
+                Error::LayoutMismatch {
+                    stored: [1; 32],
+                    current: [2; 32],
+                },
+                format!(
+                    "state layout mismatch: stored {:?}, current {:?}",
+                    [1; 32], [2; 32]
+                ),
+            ),
+        ];
+
+        for (error, message) in cases {
+            assert_eq!(error.to_string(), message);
+        }
+
+        let error = crate::Error::from(Error::Read);
+        assert_eq!(
+            error.to_string(),
+            "State error: failed to read from host storage"
+        );
+    }
+}
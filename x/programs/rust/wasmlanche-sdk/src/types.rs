@@ -19,6 +19,24 @@ impl Address {
     }
 }
 
+impl From<[u8; Address::LEN]> for Address {
+    fn from(bytes: [u8; Address::LEN]) -> Self {
+        Self(bytes)
+    }
+}
+
+impl From<Address> for [u8; Address::LEN] {
+    fn from(address: Address) -> Self {
+        address.0
+    }
+}
+
+impl AsRef<[u8]> for Address {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
 impl IntoIterator for Address {
     type Item = u8;
     type IntoIter = std::array::IntoIter<Self::Item, { Address::LEN }>;
@@ -27,3 +45,329 @@ impl IntoIterator for Address {
         IntoIterator::into_iter(self.0)
     }
 }
+
+/// A fraction of a whole in hundredths of a percent, between 0 and [`BasisPoints::MAX`].
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, BorshSerialize)]
+pub struct BasisPoints(u16);
+
+impl BasisPoints {
+    /// The number of basis points that make up the whole amount.
+    pub const MAX: u16 = 10_000;
+
+    /// Returns `None` if `value` exceeds [`BasisPoints::MAX`].
+    #[must_use]
+    pub fn new(value: u16) -> Option<Self> {
+        (value <= Self::MAX).then_some(Self(value))
+    }
+
+    /// Returns the number of basis points.
+    #[must_use]
+    pub fn get(self) -> u16 {
+        self.0
+    }
+
+    /// Returns the share of `amount`, rounded down.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn apply(self, amount: u64) -> u64 {
+        // the share never exceeds `amount` so it always fits in a u64
+        (u128::from(amount) * u128::from(self.0) / u128::from(Self::MAX)) as u64
+    }
+
+    /// Returns the share of `amount`, rounded up.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn apply_ceil(self, amount: u64) -> u64 {
+        // the share never exceeds `amount` so it always fits in a u64
+        (u128::from(amount) * u128::from(self.0)).div_ceil(u128::from(Self::MAX)) as u64
+    }
+
+    /// Returns what is left of `amount` after taking the [`apply`](Self::apply) share,
+    /// so that `apply(amount) + remainder(amount) == amount`.
+    #[must_use]
+    pub fn remainder(self, amount: u64) -> u64 {
+        amount - self.apply(amount)
+    }
+}
+
+impl BorshDeserialize for BasisPoints {
+    fn deserialize_reader<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self> {
+        let value = u16::deserialize_reader(reader)?;
+        Self::new(value).ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("basis points out of range: {value}"),
+            )
+        })
+    }
+}
+
+/// An unsigned 256-bit integer for values that can exceed a `u64`, such as
+/// prices multiplied by amounts. Serialized as 32 little-endian bytes.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default, Debug, BorshSerialize, BorshDeserialize)]
+pub struct U256([u64; 4]);
+
+impl U256 {
+    pub const ZERO: Self = Self([0; 4]);
+    pub const MAX: Self = Self([u64::MAX; 4]);
+
+    /// Returns the sum, or `None` on overflow.
+    #[must_use]
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        let mut limbs = [0; 4];
+        let mut carry = false;
+
+        for (i, limb) in limbs.iter_mut().enumerate() {
+            let (sum, overflow_a) = self.0[i].overflowing_add(rhs.0[i]);
+            let (sum, overflow_b) = sum.overflowing_add(u64::from(carry));
+            *limb = sum;
+            carry = overflow_a || overflow_b;
+        }
+
+        (!carry).then_some(Self(limbs))
+    }
+
+    /// Returns the difference, or `None` if `rhs` is greater than `self`.
+    #[must_use]
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        let (difference, borrow) = self.overflowing_sub(rhs);
+        (!borrow).then_some(difference)
+    }
+
+    /// Returns the product, or `None` on overflow.
+    #[must_use]
+    pub fn checked_mul(self, rhs: Self) -> Option<Self> {
+        Self::from_wide(self.full_mul(rhs))
+    }
+
+    /// Returns `self * numerator / denominator` rounded down, computing the
+    /// product with 512 bits so it cannot overflow. Returns `None` if the
+    /// `denominator` is zero or if the result does not fit in 256 bits.
+    #[must_use]
+    pub fn mul_div(self, numerator: Self, denominator: Self) -> Option<Self> {
+        if denominator == Self::ZERO {
+            return None;
+        }
+
+        let product = self.full_mul(numerator);
+        let mut quotient = [0_u64; 8];
+        let mut remainder = Self::ZERO;
+
+        for bit in (0..512).rev() {
+            // shift the next bit of the product into the remainder
+            let carry = remainder.0[3] >> 63 == 1;
+            remainder = remainder.shl1();
+            remainder.0[0] |= (product[bit / 64] >> (bit % 64)) & 1;
+
+            if carry || remainder >= denominator {
+                remainder = remainder.overflowing_sub(denominator).0;
+                quotient[bit / 64] |= 1 << (bit % 64);
+            }
+        }
+
+        Self::from_wide(quotient)
+    }
+
+    /// Returns the value as a `u128`, or `None` if it does not fit.
+    #[must_use]
+    pub fn to_u128(self) -> Option<u128> {
+        (self.0[2] == 0 && self.0[3] == 0)
+            .then(|| u128::from(self.0[0]) | (u128::from(self.0[1]) << 64))
+    }
+
+    /// Returns the low half of a 512-bit value if the high half is zero.
+    fn from_wide(limbs: [u64; 8]) -> Option<Self> {
+        let [a, b, c, d, high @ ..] = limbs;
+        high.iter()
+            .all(|limb| *limb == 0)
+            .then_some(Self([a, b, c, d]))
+    }
+
+    fn overflowing_sub(self, rhs: Self) -> (Self, bool) {
+        let mut limbs = [0; 4];
+        let mut borrow = false;
+
+        for (i, limb) in limbs.iter_mut().enumerate() {
+            let (difference, borrow_a) = self.0[i].overflowing_sub(rhs.0[i]);
+            let (difference, borrow_b) = difference.overflowing_sub(u64::from(borrow));
+            *limb = difference;
+            borrow = borrow_a || borrow_b;
+        }
+
+        (Self(limbs), borrow)
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn full_mul(self, rhs: Self) -> [u64; 8] {
+        let mut product = [0_u64; 8];
+
+        for (i, a) in self.0.iter().enumerate() {
+            let mut carry = 0_u128;
+            for (j, b) in rhs.0.iter().enumerate() {
+                let value = u128::from(*a) * u128::from(*b) + u128::from(product[i + j]) + carry;
+                // keep the low 64 bits, carry the rest
+                product[i + j] = value as u64;
+                carry = value >> 64;
+            }
+            product[i + 4] = carry as u64;
+        }
+
+        product
+    }
+
+    fn shl1(self) -> Self {
+        Self([
+            self.0[0] << 1,
+            (self.0[1] << 1) | (self.0[0] >> 63),
+            (self.0[2] << 1) | (self.0[1] >> 63),
+            (self.0[3] << 1) | (self.0[2] >> 63),
+        ])
+    }
+}
+
+impl Ord for U256 {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.0.iter().rev().cmp(other.0.iter().rev())
+    }
+}
+
+impl PartialOrd for U256 {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl From<u64> for U256 {
+    fn from(value: u64) -> Self {
+        Self([value, 0, 0, 0])
+    }
+}
+
+impl From<u128> for U256 {
+    #[allow(clippy::cast_possible_truncation)]
+    fn from(value: u128) -> Self {
+        Self([value as u64, (value >> 64) as u64, 0, 0])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn address_conversions() {
+        let bytes = [7; Address::LEN];
+        let address = Address::from(bytes);
+
+        assert_eq!(address, Address::new(bytes));
+        assert_eq!(<[u8; Address::LEN]>::from(address), bytes);
+        assert_eq!(address.as_ref(), &bytes);
+        assert_eq!(address.as_ref(), address.as_bytes());
+    }
+
+    #[test]
+    fn basis_points_bounds() {
+        assert_eq!(BasisPoints::new(0).map(BasisPoints::get), Some(0));
+        assert_eq!(BasisPoints::new(10_000).map(BasisPoints::get), Some(10_000));
+        assert_eq!(BasisPoints::new(10_001), None);
+
+        let bytes = borsh::to_vec(&10_001_u16).unwrap();
+        assert!(borsh::from_slice::<BasisPoints>(&bytes).is_err());
+
+        let bps = BasisPoints::new(250).unwrap();
+        let bytes = borsh::to_vec(&bps).unwrap();
+        assert_eq!(borsh::from_slice::<BasisPoints>(&bytes).unwrap(), bps);
+    }
+
+    #[test]
+    fn basis_points_rounding() {
+        let bps = BasisPoints::new(3_333).unwrap();
+
+        assert_eq!(bps.apply(10), 3);
+        assert_eq!(bps.apply_ceil(10), 4);
+        assert_eq!(bps.remainder(10), 7);
+
+        assert_eq!(bps.apply(0), 0);
+        assert_eq!(bps.apply_ceil(0), 0);
+        assert_eq!(BasisPoints::new(10_000).unwrap().apply(u64::MAX), u64::MAX);
+        assert_eq!(BasisPoints::new(0).unwrap().remainder(u64::MAX), u64::MAX);
+    }
+
+    #[test]
+    fn basis_points_split_sums_to_amount() {
+        // xorshift, so the inputs are reproducible
+        let mut state = 0x2545_f491_4f6c_dd1d_u64;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+
+        let edge_amounts = [0, 1, 9_999, 10_000, u64::MAX - 1, u64::MAX];
+        let amounts = edge_amounts.into_iter().chain(
+            std::iter::repeat_with(&mut next)
+                .take(1_000)
+                .collect::<Vec<_>>(),
+        );
+
+        for amount in amounts {
+            for value in [0, 1, 5_000, 9_999, 10_000, (next() % 10_001) as u16] {
+                let bps = BasisPoints::new(value).unwrap();
+                let share = bps.apply(amount);
+
+                assert_eq!(share + bps.remainder(amount), amount);
+                assert!(share <= amount);
+                assert!(bps.apply_ceil(amount) - share <= 1);
+            }
+        }
+    }
+
+    #[test]
+    fn u256_overflow() {
+        let one = U256::from(1_u64);
+
+        assert_eq!(U256::MAX.checked_add(one), None);
+        assert_eq!(U256::ZERO.checked_sub(one), None);
+        assert_eq!(U256::MAX.checked_mul(U256::from(2_u64)), None);
+
+        let carried = U256::from(u64::MAX).checked_add(one).unwrap();
+        assert_eq!(carried.to_u128(), Some(1 << 64));
+        assert_eq!(carried.checked_sub(one), Some(U256::from(u64::MAX)));
+
+        let product = U256::from(u128::MAX)
+            .checked_mul(U256::from(u128::MAX))
+            .unwrap();
+        assert!(product > U256::from(u128::MAX));
+        assert_eq!(product.to_u128(), None);
+    }
+
+    #[test]
+    fn u256_mul_div() {
+        let max = U256::from(u128::MAX);
+
+        // the intermediate product needs more than 256 bits
+        assert_eq!(U256::MAX.mul_div(U256::MAX, U256::MAX), Some(U256::MAX));
+        assert_eq!(max.mul_div(max, max), Some(max));
+        assert_eq!(
+            U256::from(10_u64).mul_div(U256::from(2_u64), U256::from(3_u64)),
+            Some(U256::from(6_u64))
+        );
+        assert_eq!(
+            U256::MAX.mul_div(U256::from(2_u64), U256::from(1_u64)),
+            None
+        );
+        assert_eq!(max.mul_div(max, U256::ZERO), None);
+    }
+
+    #[test]
+    fn u256_borsh_round_trip() {
+        let value = U256::from(u128::MAX - 1)
+            .checked_mul(U256::from(3_u64))
+            .unwrap();
+        let bytes = borsh::to_vec(&value).unwrap();
+
+        assert_eq!(bytes.len(), 32);
+        assert_eq!(borsh::from_slice::<U256>(&bytes).unwrap(), value);
+    }
+}
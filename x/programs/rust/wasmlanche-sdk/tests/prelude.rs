@@ -0,0 +1,26 @@
+//! A minimal program that only imports the prelude.
+
+use wasmlanche_sdk::prelude::*;
+
+#[state_keys]
+enum StateKey {
+    Owner(Address),
+}
+
+#[public]
+pub fn is_self(context: Context, target: Program) -> bool {
+    context.program.id() == target.id()
+}
+
+#[test]
+fn prelude_program_compiles() {
+    let program: Program =
+        borsh::from_slice(&[1; Program::LEN]).expect("the program should deserialize");
+    let context = Context { program };
+
+    assert!(is_self(context, program));
+
+    let key = StateKey::Owner(Address::new([2; Address::LEN])).to_vec();
+    assert_eq!(key[0], 0);
+    assert_eq!(&key[1..], &[2; Address::LEN]);
+}
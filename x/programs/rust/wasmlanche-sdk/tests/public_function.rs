@@ -1,9 +1,10 @@
 use std::{
+    collections::HashMap,
     path::{Path, PathBuf},
     process::Command,
 };
-use wasmlanche_sdk::{Context, HostPtr, Program};
-use wasmtime::{Instance, Module, Store, TypedFunc};
+use wasmlanche_sdk::{types::Address, Context, HostPtr, Program};
+use wasmtime::{Caller, Extern, Instance, Linker, Memory, Module, Store, TypedFunc};
 
 const WASM_TARGET: &str = "wasm32-unknown-unknown";
 const TEST_PKG: &str = "test-crate";
@@ -58,27 +59,83 @@ fn public_functions() {
     };
 
     assert!(test_crate.always_true(context_ptr));
+    assert!(test_crate.renamed_true(context_ptr));
+    assert!(test_crate.always_one(context_ptr));
+    assert!(test_crate.always_yes(context_ptr));
 
     let combined_binary_digits = test_crate.combine_last_bit_of_each_id_byte(context_ptr);
     assert_eq!(combined_binary_digits, u32::MAX);
+
+    assert_eq!(test_crate.count_once(context_ptr, 1, false), 1);
+    // a repeated key does not run again
+    assert_eq!(test_crate.count_once(context_ptr, 1, false), 1);
+    assert_eq!(test_crate.count_once(context_ptr, 2, false), 2);
+    // a failed run does not record the key, so the retry goes through
+    assert_eq!(test_crate.count_once(context_ptr, 3, true), -1);
+    assert_eq!(test_crate.count_once(context_ptr, 3, false), 3);
+
+    // the bytes of a negative i64 decode as a huge u64 and must not pass the balance check
+    let negative_one = (-1_i64).to_le_bytes().to_vec();
+    assert_eq!(borsh::from_slice::<u64>(&negative_one).unwrap(), u64::MAX);
+    assert_eq!(test_crate.spend(context_ptr, negative_one), -1);
+    assert_eq!(
+        test_crate.spend(context_ptr, 30_u64.to_le_bytes().to_vec()),
+        70
+    );
+
+    let value = test_crate.allocate(borsh::to_vec(&0x0102_0304_u64).unwrap());
+    assert!(test_crate.raw_round_trip(context_ptr, value));
+
+    assert_eq!(test_crate.check_stored_layout(context_ptr, false), 0);
+    // a hash stored by a different layout is reported with both hashes
+    assert_eq!(test_crate.check_stored_layout(context_ptr, true), 1);
+
+    let owner = test_crate.allocate(vec![4; Address::LEN]);
+    let other = test_crate.allocate(vec![5; Address::LEN]);
+    assert!(!test_crate.is_record_owner(context_ptr, owner));
+    assert!(test_crate.store_record(context_ptr, owner));
+    assert!(test_crate.is_record_owner(context_ptr, owner));
+    assert!(!test_crate.is_record_owner(context_ptr, other));
+
+    let of = test_crate.allocate(vec![6; Address::LEN]);
+    let amount = test_crate.allocate(21_u64.to_le_bytes().to_vec());
+    assert_eq!(test_crate.call_double(context_ptr, of, amount), 42);
 }
 
 type AllocParam = i32;
 type AllocReturn = i32;
 
+/// The program's storage, keyed by the raw key bytes.
+type HostState = HashMap<Vec<u8>, Vec<u8>>;
+
 struct TestCrate {
-    store: Store<()>,
+    store: Store<HostState>,
     instance: Instance,
     allocate_func: TypedFunc<AllocParam, AllocReturn>,
     always_true_func: TypedFunc<HostPtr, i64>,
+    renamed_true_func: TypedFunc<HostPtr, i64>,
+    always_one_func: TypedFunc<HostPtr, i64>,
+    always_yes_func: TypedFunc<HostPtr, i64>,
     combine_last_bit_of_each_id_byte_func: TypedFunc<HostPtr, u32>,
+    count_once_func: TypedFunc<(HostPtr, HostPtr, HostPtr), i64>,
+    spend_func: TypedFunc<(HostPtr, HostPtr), i64>,
+    store_record_func: TypedFunc<(HostPtr, HostPtr), i32>,
+    is_record_owner_func: TypedFunc<(HostPtr, HostPtr), i32>,
+    call_double_func: TypedFunc<(HostPtr, HostPtr, HostPtr), i64>,
+    raw_round_trip_func: TypedFunc<(HostPtr, HostPtr), i32>,
+    check_stored_layout_func: TypedFunc<(HostPtr, HostPtr), i64>,
 }
 
 impl TestCrate {
     fn new(wasm_path: impl AsRef<Path>) -> Self {
-        let mut store: Store<()> = Store::default();
+        let mut store: Store<HostState> = Store::default();
         let module = Module::from_file(store.engine(), wasm_path).expect("failed to load wasm");
-        let instance = Instance::new(&mut store, &module, &[]).expect("failed to instantiate wasm");
+        let mut linker = Linker::new(store.engine());
+        link_state(&mut linker);
+        link_program(&mut linker);
+        let instance = linker
+            .instantiate(&mut store, &module)
+            .expect("failed to instantiate wasm");
 
         let allocate_func = instance
             .get_typed_func::<AllocParam, AllocReturn>(&mut store, "alloc")
@@ -87,16 +144,56 @@ impl TestCrate {
         let always_true_func = instance
             .get_typed_func::<i64, i64>(&mut store, "always_true_guest")
             .expect("failed to find `always_true` function");
+        let renamed_true_func = instance
+            .get_typed_func::<i64, i64>(&mut store, "renamed_true_guest")
+            .expect("failed to find `renamed_true` function");
+        let always_one_func = instance
+            .get_typed_func::<i64, i64>(&mut store, "always_one_guest")
+            .expect("failed to find `always_one` alias");
+        let always_yes_func = instance
+            .get_typed_func::<i64, i64>(&mut store, "always_yes_guest")
+            .expect("failed to find `always_yes` alias");
         let combine_last_bit_of_each_id_byte_func = instance
             .get_typed_func::<i64, u32>(&mut store, "combine_last_bit_of_each_id_byte_guest")
             .expect("combine_last_bit_of_each_id_byte should be a function");
+        let count_once_func = instance
+            .get_typed_func::<(i64, i64, i64), i64>(&mut store, "count_once_guest")
+            .expect("failed to find `count_once` function");
+        let spend_func = instance
+            .get_typed_func::<(i64, i64), i64>(&mut store, "spend_guest")
+            .expect("failed to find `spend` function");
+        let store_record_func = instance
+            .get_typed_func::<(i64, i64), i32>(&mut store, "store_record_guest")
+            .expect("failed to find `store_record` function");
+        let is_record_owner_func = instance
+            .get_typed_func::<(i64, i64), i32>(&mut store, "is_record_owner_guest")
+            .expect("failed to find `is_record_owner` function");
+        let call_double_func = instance
+            .get_typed_func::<(i64, i64, i64), i64>(&mut store, "call_double_guest")
+            .expect("failed to find `call_double` function");
+        let raw_round_trip_func = instance
+            .get_typed_func::<(i64, i64), i32>(&mut store, "raw_round_trip_guest")
+            .expect("failed to find `raw_round_trip` function");
+        let check_stored_layout_func = instance
+            .get_typed_func::<(i64, i64), i64>(&mut store, "check_stored_layout_guest")
+            .expect("failed to find `check_stored_layout` function");
 
         Self {
             store,
             instance,
             allocate_func,
             always_true_func,
+            renamed_true_func,
+            always_one_func,
+            always_yes_func,
             combine_last_bit_of_each_id_byte_func,
+            count_once_func,
+            spend_func,
+            store_record_func,
+            is_record_owner_func,
+            call_double_func,
+            raw_round_trip_func,
+            check_stored_layout_func,
         }
     }
 
@@ -124,9 +221,183 @@ impl TestCrate {
             == true as i64
     }
 
+    fn renamed_true(&mut self, ptr: HostPtr) -> bool {
+        self.renamed_true_func
+            .call(&mut self.store, ptr)
+            .expect("failed to call `renamed_true` function")
+            == true as i64
+    }
+
+    fn always_one(&mut self, ptr: HostPtr) -> bool {
+        self.always_one_func
+            .call(&mut self.store, ptr)
+            .expect("failed to call `always_one` alias")
+            == true as i64
+    }
+
+    fn always_yes(&mut self, ptr: HostPtr) -> bool {
+        self.always_yes_func
+            .call(&mut self.store, ptr)
+            .expect("failed to call `always_yes` alias")
+            == true as i64
+    }
+
     fn combine_last_bit_of_each_id_byte(&mut self, ptr: HostPtr) -> u32 {
         self.combine_last_bit_of_each_id_byte_func
             .call(&mut self.store, ptr)
             .expect("failed to call `combine_last_bit_of_each_id_byte` function")
     }
+
+    fn count_once(&mut self, ptr: HostPtr, key: u64, fail: bool) -> i64 {
+        let key = self.allocate(borsh::to_vec(&key).expect("failed to serialize key"));
+        let fail = self.allocate(borsh::to_vec(&fail).expect("failed to serialize flag"));
+        self.count_once_func
+            .call(&mut self.store, (ptr, key, fail))
+            .expect("failed to call `count_once` function")
+    }
+
+    fn spend(&mut self, ptr: HostPtr, amount: Vec<u8>) -> i64 {
+        let amount = self.allocate(amount);
+        self.spend_func
+            .call(&mut self.store, (ptr, amount))
+            .expect("failed to call `spend` function")
+    }
+
+    fn store_record(&mut self, ptr: HostPtr, owner: HostPtr) -> bool {
+        self.store_record_func
+            .call(&mut self.store, (ptr, owner))
+            .expect("failed to call `store_record` function")
+            == true as i32
+    }
+
+    fn is_record_owner(&mut self, ptr: HostPtr, owner: HostPtr) -> bool {
+        self.is_record_owner_func
+            .call(&mut self.store, (ptr, owner))
+            .expect("failed to call `is_record_owner` function")
+            == true as i32
+    }
+
+    fn call_double(&mut self, ptr: HostPtr, of: HostPtr, amount: HostPtr) -> i64 {
+        self.call_double_func
+            .call(&mut self.store, (ptr, of, amount))
+            .expect("failed to call `call_double` function")
+    }
+
+    fn raw_round_trip(&mut self, ptr: HostPtr, value: HostPtr) -> bool {
+        self.raw_round_trip_func
+            .call(&mut self.store, (ptr, value))
+            .expect("failed to call `raw_round_trip` function")
+            == true as i32
+    }
+
+    fn check_stored_layout(&mut self, ptr: HostPtr, tamper: bool) -> i64 {
+        let tamper = self.allocate(borsh::to_vec(&tamper).expect("failed to serialize flag"));
+        self.check_stored_layout_func
+            .call(&mut self.store, (ptr, tamper))
+            .expect("failed to call `check_stored_layout` function")
+    }
+}
+
+/// Links an in-memory implementation of the `state` host module.
+fn link_state(linker: &mut Linker<HostState>) {
+    linker
+        .func_wrap(
+            "state",
+            "put",
+            |mut caller: Caller<'_, HostState>, _program: i64, key: i64, value: i64| {
+                let key = read_bytes(&mut caller, key);
+                let value = read_bytes(&mut caller, value);
+                caller.data_mut().insert(key, value);
+                0_i64
+            },
+        )
+        .expect("failed to link `put`");
+
+    linker
+        .func_wrap(
+            "state",
+            "get",
+            |mut caller: Caller<'_, HostState>, _program: i64, key: i64| {
+                let key = read_bytes(&mut caller, key);
+                match caller.data().get(&key).cloned() {
+                    Some(value) => write_bytes(&mut caller, &value),
+                    None => -1_i64,
+                }
+            },
+        )
+        .expect("failed to link `get`");
+
+    linker
+        .func_wrap(
+            "state",
+            "delete",
+            |mut caller: Caller<'_, HostState>, _program: i64, key: i64| {
+                let key = read_bytes(&mut caller, key);
+                caller.data_mut().remove(&key);
+                0_i64
+            },
+        )
+        .expect("failed to link `delete`");
+}
+
+/// Links a `program` host module whose only callable function is `double`.
+/// It expects an address and a `u64`, each prefixed with its big-endian `u32`
+/// length, and returns twice the `u64`. Any other call returns -1.
+fn link_program(linker: &mut Linker<HostState>) {
+    linker
+        .func_wrap(
+            "program",
+            "call_program",
+            |mut caller: Caller<'_, HostState>,
+             _target: i64,
+             function: i64,
+             args: i64,
+             max_units: i64| {
+                let function = read_bytes(&mut caller, function);
+                let args = read_bytes(&mut caller, args);
+
+                let mut expected = (Address::LEN as u32).to_be_bytes().to_vec();
+                expected.extend_from_slice(&[6; Address::LEN]);
+                expected.extend_from_slice(&8_u32.to_be_bytes());
+
+                match args.strip_prefix(expected.as_slice()) {
+                    Some(amount) if function == b"double" && max_units == 1000 => {
+                        let amount: u64 = borsh::from_slice(amount).expect("invalid amount");
+                        amount as i64 * 2
+                    }
+                    _ => -1,
+                }
+            },
+        )
+        .expect("failed to link `call_program`");
+}
+
+fn memory(caller: &mut Caller<'_, HostState>) -> Memory {
+    caller
+        .get_export("memory")
+        .and_then(Extern::into_memory)
+        .expect("failed to get memory")
+}
+
+fn read_bytes(caller: &mut Caller<'_, HostState>, ptr: HostPtr) -> Vec<u8> {
+    let offset = (ptr & 0xffff_ffff) as usize;
+    let len = (ptr >> 32) as usize;
+    memory(caller).data(&caller)[offset..offset + len].to_vec()
+}
+
+fn write_bytes(caller: &mut Caller<'_, HostState>, bytes: &[u8]) -> HostPtr {
+    let offset = caller
+        .get_export("alloc")
+        .and_then(Extern::into_func)
+        .expect("failed to find `alloc` function")
+        .typed::<AllocParam, AllocReturn>(&caller)
+        .expect("`alloc` should have the expected signature")
+        .call(&mut *caller, bytes.len() as AllocParam)
+        .expect("failed to allocate memory");
+
+    memory(caller)
+        .write(&mut *caller, offset as usize, bytes)
+        .expect("failed to write data to memory");
+
+    ((bytes.len() as HostPtr) << 32) | offset as HostPtr
 }
@@ -0,0 +1,182 @@
+use wasmlanche_sdk::{state::Key, state_keys, types::Address};
+
+#[state_keys]
+enum StateKey {
+    Name,
+    Balance(Address),
+}
+
+#[state_keys(hashed)]
+enum HashedStateKey {
+    Name,
+    Balance(Address),
+    Allowance(Address, Address),
+}
+
+#[test]
+fn raw_keys_append_fields_to_prefix() {
+    assert_eq!(StateKey::Name.to_vec(), vec![0]);
+
+    let key = StateKey::Balance(Address::new([1; Address::LEN])).to_vec();
+    assert_eq!(key[0], 1);
+    assert_eq!(&key[1..], &[1; Address::LEN]);
+}
+
+#[test]
+fn describe_lists_prefixes_and_names() {
+    assert_eq!(
+        HashedStateKey::describe(),
+        vec![(0, "Name"), (1, "Balance"), (2, "Allowance")]
+    );
+}
+
+// These encodings must never change, otherwise programs lose access to their state.
+#[test]
+fn hashed_keys_are_pinned() {
+    assert_eq!(HashedStateKey::Name.to_vec(), vec![0]);
+
+    let key: Key = HashedStateKey::Balance(Address::new([1; Address::LEN])).into();
+    assert_eq!(
+        &*key,
+        &[
+            1, 0x72, 0xcd, 0x6e, 0x84, 0x22, 0xc4, 0x07, 0xfb, 0x6d, 0x09, 0x86, 0x90, 0xf1, 0x13,
+            0x0b, 0x7d
+        ]
+    );
+
+    let key = HashedStateKey::Allowance(
+        Address::new([1; Address::LEN]),
+        Address::new([2; Address::LEN]),
+    )
+    .to_vec();
+    assert_eq!(key.len(), 1 + wasmlanche_sdk::state::HASHED_KEY_LEN);
+    assert_eq!(
+        key,
+        vec![
+            2, 0xf8, 0x18, 0xaf, 0xd3, 0x7a, 0x6d, 0xc3, 0xbc, 0x92, 0xfb, 0x44, 0x73, 0x10, 0x11,
+            0x27, 0x70
+        ]
+    );
+}
+
+#[state_keys]
+enum IntegerStateKey {
+    Metadata(u64),
+    Position(Address, u16),
+}
+
+#[test]
+fn integer_fields_are_big_endian() {
+    assert_eq!(
+        IntegerStateKey::Metadata(0x0102_0304_0506_0708).to_vec(),
+        vec![0, 1, 2, 3, 4, 5, 6, 7, 8]
+    );
+
+    let key = IntegerStateKey::Position(Address::new([9; Address::LEN]), 0x0a0b).to_vec();
+    assert_eq!(key[0], 1);
+    assert_eq!(&key[1..=Address::LEN], &[9; Address::LEN]);
+    assert_eq!(&key[Address::LEN + 1..], &[0x0a, 0x0b]);
+}
+
+#[state_keys]
+enum MixedStateKey {
+    #[key(raw)]
+    Balance(Address, u64),
+    #[key(borsh)]
+    Label(Address, u64),
+    #[key(borsh)]
+    Paused(bool),
+}
+
+#[state_keys(hashed)]
+enum MixedHashedStateKey {
+    #[key(raw)]
+    Balance(Address),
+    Allowance(Address, Address),
+}
+
+#[test]
+fn per_variant_encodings() {
+    let address = Address::new([3; Address::LEN]);
+
+    let raw = MixedStateKey::Balance(address, 0x0102).to_vec();
+    let mut expected = vec![0];
+    expected.extend_from_slice(&[3; Address::LEN]);
+    expected.extend_from_slice(&[0, 0, 0, 0, 0, 0, 1, 2]);
+    assert_eq!(raw, expected);
+
+    // borsh writes integers little-endian
+    let borsh = MixedStateKey::Label(address, 0x0102).to_vec();
+    let mut expected = vec![1];
+    expected.extend_from_slice(&[3; Address::LEN]);
+    expected.extend_from_slice(&[2, 1, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(borsh, expected);
+
+    assert_eq!(MixedStateKey::Paused(true).to_vec(), vec![2, 1]);
+
+    let raw = MixedHashedStateKey::Balance(address).to_vec();
+    assert_eq!(raw[0], 0);
+    assert_eq!(&raw[1..], &[3; Address::LEN]);
+    assert_eq!(
+        MixedHashedStateKey::Allowance(address, address)
+            .to_vec()
+            .len(),
+        1 + wasmlanche_sdk::state::HASHED_KEY_LEN
+    );
+}
+
+// these enums only exist for their layout hashes
+#[allow(dead_code)]
+mod layout {
+    use sha2::{Digest, Sha256};
+    use wasmlanche_sdk::{state_keys, types::Address};
+
+    #[state_keys]
+    pub enum Original {
+        Name,
+        Balance(Address),
+    }
+
+    #[state_keys]
+    pub enum SameLayout {
+        Name,
+        Balance(Address),
+    }
+
+    #[state_keys]
+    pub enum ChangedPayload {
+        Name,
+        Balance(u64),
+    }
+
+    #[state_keys(hashed)]
+    pub enum ChangedMode {
+        Name,
+        Balance(Address),
+    }
+
+    #[test]
+    fn layout_hash_is_pinned() {
+        let expected: [u8; 32] = Sha256::digest("raw;0:Name();1:Balance(Address)").into();
+
+        assert_eq!(Original::LAYOUT_HASH, expected);
+        assert_eq!(Original::layout_hash(), SameLayout::layout_hash());
+    }
+
+    #[state_keys]
+    pub enum ChangedEncoding {
+        Name,
+        #[key(borsh)]
+        Balance(Address),
+    }
+
+    #[test]
+    fn layout_hash_tracks_changes() {
+        let expected: [u8; 32] = Sha256::digest("raw;0:Name();1:Balance(Address)@borsh").into();
+        assert_eq!(ChangedEncoding::LAYOUT_HASH, expected);
+        assert_ne!(Original::layout_hash(), ChangedEncoding::layout_hash());
+
+        assert_ne!(Original::layout_hash(), ChangedPayload::layout_hash());
+        assert_ne!(Original::layout_hash(), ChangedMode::layout_hash());
+    }
+}
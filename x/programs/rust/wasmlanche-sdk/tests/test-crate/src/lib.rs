@@ -1,12 +1,36 @@
 #![no_std]
 
-use wasmlanche_sdk::{public, Context};
+extern crate alloc;
+
+use alloc::{string::String, vec, vec::Vec};
+use borsh::BorshSerialize;
+use wasmlanche_sdk::{
+    public,
+    state::{Error, Key},
+    state_keys,
+    types::Address,
+    Context,
+};
+
+#[state_keys]
+enum StateKey {
+    /// The counter of [`count_once`].
+    Counter,
+    /// The layout hash checked by [`check_stored_layout`].
+    Layout,
+}
 
 #[public]
 pub fn always_true(_: Context) -> i64 {
     true as i64
 }
 
+/// Exported under its own name and under two aliases.
+#[public(alias = "always_one", alias = "r#always_yes")]
+pub fn renamed_true(_: Context) -> i64 {
+    true as i64
+}
+
 #[public]
 pub fn combine_last_bit_of_each_id_byte(context: Context) -> u32 {
     context
@@ -16,3 +40,139 @@ pub fn combine_last_bit_of_each_id_byte(context: Context) -> u32 {
         .map(|byte| *byte as u32)
         .fold(0, |acc, byte| (acc << 1) + (byte & 1))
 }
+
+/// Increments a counter the first time `key` is seen and returns the counter.
+/// With `fail` the increment is rejected and -1 is returned.
+#[public]
+pub fn count_once(context: Context, key: u64, fail: bool) -> i64 {
+    let state = context.program.state();
+    let counter = || StateKey::Counter;
+    let mut seen = vec![1];
+    seen.extend_from_slice(&key.to_be_bytes());
+
+    let result = state.once(Key::new(seen), || {
+        if fail {
+            return Err(Error::Other(String::from("rejected")));
+        }
+
+        let count = state.get::<i64, _>(counter()).unwrap_or_default();
+        state.store(counter(), &(count + 1))
+    });
+
+    match result {
+        Ok(_) => state.get(counter()).unwrap_or_default(),
+        Err(_) => -1,
+    }
+}
+
+/// Spends `amount` from a balance that starts at 100 and returns the remaining
+/// balance, or -1 if the balance is too low.
+#[public]
+pub fn spend(context: Context, amount: u64) -> i64 {
+    let state = context.program.state();
+    let balance_key = || Key::new(vec![2]);
+    let balance = state.get::<u64, _>(balance_key()).unwrap_or(100);
+
+    match balance.checked_sub(amount) {
+        Some(balance) => {
+            state
+                .store(balance_key(), &balance)
+                .expect("failed to store balance");
+            balance as i64
+        }
+        None => -1,
+    }
+}
+
+/// A record whose owner sits after a fixed-size field and before a large one.
+#[derive(BorshSerialize)]
+struct Record {
+    amount: u64,
+    owner: Address,
+    memo: [u8; 1024],
+}
+
+/// Stores a record owned by `owner` under a fixed key.
+#[public]
+pub fn store_record(context: Context, owner: Address) -> bool {
+    let record = Record {
+        amount: 7,
+        owner,
+        memo: [0; 1024],
+    };
+
+    context
+        .program
+        .state()
+        .store(Key::new(vec![3]), &record)
+        .is_ok()
+}
+
+/// Returns whether the stored record is owned by `owner`, reading only the owner.
+#[public]
+pub fn is_record_owner(context: Context, owner: Address) -> bool {
+    let stored = context
+        .program
+        .state()
+        .peek_field::<Address, _>(Key::new(vec![3]), 8, Address::LEN)
+        .expect("failed to read owner");
+
+    stored == Some(owner)
+}
+
+/// Calls `double` on the program itself through the call builder.
+#[public]
+pub fn call_double(context: Context, of: Address, amount: u64) -> i64 {
+    context
+        .program
+        .call("double", 1000)
+        .arg(&of)
+        .arg(&amount)
+        .invoke()
+        .expect("failed to call `double`")
+}
+
+/// Stores `value`, reads it back undecoded and returns whether the raw bytes,
+/// the decoded value and a read of a missing key are as expected.
+#[public]
+pub fn raw_round_trip(context: Context, value: u64) -> bool {
+    let state = context.program.state();
+    state
+        .store(Key::new(vec![5]), &value)
+        .expect("failed to store value");
+
+    let raw = state
+        .get_raw_typed::<u64, _>(Key::new(vec![5]))
+        .expect("failed to read value");
+    let missing = state.get_raw_typed::<u64, _>(Key::new(vec![6]));
+
+    raw.bytes() == value.to_le_bytes()
+        && raw.decode().ok() == Some(value)
+        && matches!(missing, Err(Error::Read))
+}
+
+/// Stores the layout hash of [`StateKey`], with its first byte flipped if
+/// `tamper` is set, and checks it. Returns 0 if the check passes, 1 if it
+/// reports the stored and current hashes and -1 on any other result.
+#[public]
+pub fn check_stored_layout(context: Context, tamper: bool) -> i64 {
+    let state = context.program.state();
+    let mut hash = StateKey::LAYOUT_HASH;
+    if tamper {
+        hash[0] ^= 0xff;
+    }
+
+    state
+        .store(StateKey::Layout, &hash)
+        .expect("failed to store layout hash");
+
+    match StateKey::check_layout(&state, StateKey::Layout) {
+        Ok(()) => 0,
+        Err(Error::LayoutMismatch { stored, current })
+            if stored == hash && current == StateKey::LAYOUT_HASH =>
+        {
+            1
+        }
+        Err(_) => -1,
+    }
+}